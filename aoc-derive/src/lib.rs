@@ -0,0 +1,149 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Fields, Lit, Token, parse_macro_input, punctuated::Punctuated,
+};
+
+/// Derive `aoc::two::GridCell`, `From<u8>` and `Display` for a grid cell enum,
+/// so it can be used with `DenseField::from_lines` and `debug_print`.
+///
+/// Every variant needs a `#[cell(b'#')]` attribute giving the byte it's parsed
+/// from. Several bytes can be given, like `#[cell(b'.', b' ')]`, in which case
+/// the first is used when displaying. Variants with fields are created with
+/// each field set to its default.
+#[proc_macro_derive(GridCell, attributes(cell))]
+pub fn derive_grid_cell(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    grid_cell(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn grid_cell(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "GridCell can only be derived for enums",
+        ));
+    };
+
+    let mut parse_arms = vec![];
+    let mut byte_arms = vec![];
+    let mut expected = vec![];
+
+    for variant in &data.variants {
+        let bytes = cell_bytes(variant)?;
+        let ident = &variant.ident;
+
+        for b in &bytes {
+            if expected.contains(b) {
+                return Err(Error::new_spanned(
+                    variant,
+                    format!("byte '{}' is used by more than one variant", *b as char),
+                ));
+            }
+            expected.push(*b);
+        }
+
+        let (construct, pattern) = match &variant.fields {
+            Fields::Unit => (quote!(#name::#ident), quote!(#name::#ident)),
+            Fields::Unnamed(fields) => {
+                let defaults = fields
+                    .unnamed
+                    .iter()
+                    .map(|_| quote!(::core::default::Default::default()));
+                (
+                    quote!(#name::#ident(#(#defaults),*)),
+                    quote!(#name::#ident(..)),
+                )
+            }
+            Fields::Named(fields) => {
+                let defaults = fields.named.iter().map(|f| {
+                    let f = &f.ident;
+                    quote!(#f: ::core::default::Default::default())
+                });
+                (
+                    quote!(#name::#ident { #(#defaults),* }),
+                    quote!(#name::#ident { .. }),
+                )
+            }
+        };
+
+        let display = bytes[0];
+        parse_arms.push(quote!(#(#bytes)|* => ::core::result::Result::Ok(#construct)));
+        byte_arms.push(quote!(#pattern => #display));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::aoc::two::GridCell for #name #ty_generics #where_clause {
+            const BYTES: &'static [u8] = &[#(#expected),*];
+
+            fn try_from_byte(value: u8) -> ::core::result::Result<Self, ::aoc::two::UnknownCell> {
+                match value {
+                    #(#parse_arms,)*
+                    byte => ::core::result::Result::Err(::aoc::two::UnknownCell {
+                        byte,
+                        cell: ::core::stringify!(#name),
+                        expected: Self::BYTES,
+                    }),
+                }
+            }
+
+            fn to_byte(&self) -> u8 {
+                match self {
+                    #(#byte_arms,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<u8> for #name #ty_generics #where_clause {
+            fn from(value: u8) -> Self {
+                match <Self as ::aoc::two::GridCell>::try_from_byte(value) {
+                    ::core::result::Result::Ok(cell) => cell,
+                    ::core::result::Result::Err(e) => ::core::panic!("{e}"),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let byte = <Self as ::aoc::two::GridCell>::to_byte(self);
+                ::core::fmt::Write::write_char(f, byte as char)
+            }
+        }
+    })
+}
+
+/// Get the bytes from the `#[cell(...)]` attribute of a variant. Accepts byte
+/// literals and ASCII char literals.
+fn cell_bytes(variant: &syn::Variant) -> syn::Result<Vec<u8>> {
+    let attr = variant
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("cell"))
+        .ok_or_else(|| {
+            Error::new_spanned(
+                &variant.ident,
+                "missing #[cell(b'?')] attribute for GridCell variant",
+            )
+        })?;
+
+    let lits = attr.parse_args_with(Punctuated::<Lit, Token![,]>::parse_terminated)?;
+    if lits.is_empty() {
+        return Err(Error::new_spanned(attr, "expected at least one byte"));
+    }
+
+    lits.iter()
+        .map(|lit| match lit {
+            Lit::Byte(b) => Ok(b.value()),
+            Lit::Char(c) if c.value().is_ascii() => Ok(c.value() as u8),
+            _ => Err(Error::new(
+                lit.span(),
+                "expected a byte literal like b'#' or an ascii char",
+            )),
+        })
+        .collect()
+}
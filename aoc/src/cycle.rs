@@ -0,0 +1,140 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A cycle found by `run_until_by_key`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Step the cycle first started on.
+    pub start: usize,
+    /// Number of steps before it repeats.
+    pub len: usize,
+    /// How many whole cycles were skipped rather than simulated.
+    pub skipped: usize,
+}
+
+/// Apply `step` to the state `n` times, remembering every state seen so
+/// that as soon as one repeats we can jump ahead by whole cycles. Only the
+/// leftover steps after that are simulated.
+pub fn run_until<S: Clone + Hash + Eq>(state: S, step: impl FnMut(&mut S), n: usize) -> S {
+    run_until_by_key(state, step, n, S::clone).0
+}
+
+/// Same as `run_until`, but only remembers a key for each state rather than
+/// the state itself, for when states are large or expensive to hash. The key
+/// needs to be enough to tell that the states that matter repeat, eg the top
+/// few rows of a pile plus where we are in the input.
+///
+/// Also returns the cycle that was found, if any, so that things that grow
+/// each cycle without being part of the key can be extrapolated, using
+/// `skipped` times however much they grow over one cycle.
+pub fn run_until_by_key<S, K: Hash + Eq>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    n: usize,
+    mut key: impl FnMut(&S) -> K,
+) -> (S, Option<Cycle>) {
+    let mut seen = HashMap::new();
+    for i in 0..n {
+        if let Some(start) = seen.insert(key(&state), i) {
+            let len = i - start;
+            let skipped = (n - i) / len;
+            for _ in 0..(n - i) % len {
+                step(&mut state);
+            }
+            return (
+                state,
+                Some(Cycle {
+                    start,
+                    len,
+                    skipped,
+                }),
+            );
+        }
+        step(&mut state);
+    }
+    (state, None)
+}
+
+/// Same as `run_until`, but uses Brent's algorithm to find the cycle, which
+/// only keeps a couple of states around rather than all of them. Costs more
+/// steps than `run_until`, so it's best when states are big but cheap to
+/// step.
+pub fn run_until_brent<S: Clone + Eq>(state: S, mut step: impl FnMut(&mut S), n: usize) -> S {
+    // The hare runs ahead while the tortoise teleports to it every power of
+    // two steps. Once they meet, the hare is inside the cycle and `len` is
+    // exactly its length.
+    let mut tortoise = state.clone();
+    let mut hare = state;
+    let mut power = 1;
+    let mut len = 0;
+    for i in 0..n {
+        step(&mut hare);
+        len += 1;
+        if hare == tortoise {
+            for _ in 0..(n - i - 1) % len {
+                step(&mut hare);
+            }
+            return hare;
+        }
+        if len == power {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+    }
+    hare
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cycle::{Cycle, run_until, run_until_brent, run_until_by_key};
+
+    // Goes 0, 1, 2, 3, 4 then cycles 5..12 forever.
+    fn step(x: &mut u64) {
+        *x = if *x == 11 { 5 } else { *x + 1 };
+    }
+
+    fn naive(n: usize) -> u64 {
+        let mut x = 0;
+        for _ in 0..n {
+            step(&mut x);
+        }
+        x
+    }
+
+    #[test]
+    fn cycles() {
+        for n in (0..40).chain([1_000_000_000_000]) {
+            let expected = if n < 40 {
+                naive(n)
+            } else {
+                naive(5 + (n - 5) % 7)
+            };
+            assert_eq!(run_until(0, step, n), expected, "{n}");
+            assert_eq!(run_until_brent(0, step, n), expected, "{n}");
+        }
+    }
+
+    #[test]
+    fn by_key() {
+        // Count how far we've gone too, which never repeats, so key on the
+        // position in the cycle only.
+        let step = |(x, total): &mut (u64, u64)| {
+            step(x);
+            *total += 1;
+        };
+        let (state, cycle) = run_until_by_key((0, 0), step, 100, |s| s.0);
+        let cycle = cycle.unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 5,
+                len: 7,
+                skipped: 12
+            }
+        );
+        assert_eq!(state.0, naive(100));
+        assert_eq!(state.1 + (cycle.skipped * cycle.len) as u64, 100);
+
+        assert_eq!(run_until_by_key(0, |x| *x += 1, 10, |x| *x), (10, None));
+    }
+}
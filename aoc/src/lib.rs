@@ -11,10 +11,21 @@ use std::{
 
 use reqwest::Method;
 
+// Lets code generated by aoc-derive refer to `::aoc` from within this crate too.
+extern crate self as aoc;
+
+pub mod cycle;
 mod disjoint_set;
+mod nested;
+pub mod queue;
+pub mod search;
+pub mod sections;
+pub mod three;
 pub mod two;
 
 pub use disjoint_set::*;
+pub use nested::*;
+pub use sections::{Sections, SectionsError};
 
 pub fn text(path: impl AsRef<Path>) -> String {
     let mut input = BufReader::new(File::open(path).unwrap());
@@ -34,7 +45,15 @@ pub fn lines(path: impl AsRef<Path>) -> Vec<String> {
 
 /// Parse input as blocks of lines, each block should be separated be a blank line.
 pub fn line_blocks(path: impl AsRef<Path>) -> Vec<Vec<String>> {
-    let input = lines(path);
+    blocks(lines(path))
+}
+
+/// Same as line_blocks but for string input, useful for tests.
+pub fn line_blocks_from_str(input: &str) -> Vec<Vec<String>> {
+    blocks(lines_from_str(input))
+}
+
+fn blocks(input: Vec<String>) -> Vec<Vec<String>> {
     let mut blocks = vec![];
     let mut current = vec![];
 
@@ -0,0 +1,418 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{Debug, Display},
+    iter::Peekable,
+    str::{CharIndices, FromStr},
+};
+
+/// A tree of nested lists, like `[[1],[2,[3]]]`. Leaves are any type that can
+/// be parsed with FromStr.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Nested<T> {
+    Leaf(T),
+    List(Vec<Nested<T>>),
+}
+
+/// Characters used to delimit nested lists. Whitespace between tokens is
+/// ignored. If the separator is whitespace then any run of whitespace between
+/// values counts as one separator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delims {
+    pub open: char,
+    pub close: char,
+    pub sep: char,
+}
+
+impl Default for Delims {
+    fn default() -> Self {
+        Delims {
+            open: '[',
+            close: ']',
+            sep: ',',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestedError {
+    /// Found a character we didn't expect, or None if the input ended early.
+    Unexpected { pos: usize, found: Option<char> },
+    /// A leaf failed to parse with FromStr.
+    Leaf {
+        pos: usize,
+        token: String,
+        message: String,
+    },
+}
+
+impl Display for NestedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NestedError::Unexpected {
+                pos,
+                found: Some(c),
+            } => write!(f, "unexpected '{c}' at {pos}"),
+            NestedError::Unexpected { pos, found: None } => {
+                write!(f, "unexpected end of input at {pos}")
+            }
+            NestedError::Leaf {
+                pos,
+                token,
+                message,
+            } => write!(f, "bad leaf '{token}' at {pos}: {message}"),
+        }
+    }
+}
+
+impl Error for NestedError {}
+
+impl<T: FromStr> Nested<T>
+where
+    T::Err: Debug,
+{
+    /// Parse with the usual `[`, `]` and `,` delimiters.
+    pub fn parse(s: &str) -> Result<Self, NestedError> {
+        Self::parse_with(s, Delims::default())
+    }
+
+    pub fn parse_with(s: &str, delims: Delims) -> Result<Self, NestedError> {
+        let mut parser = Parser {
+            input: s,
+            chars: s.char_indices().peekable(),
+            delims,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(value),
+            Some((pos, c)) => Err(NestedError::Unexpected {
+                pos,
+                found: Some(c),
+            }),
+        }
+    }
+}
+
+impl<T: FromStr> FromStr for Nested<T>
+where
+    T::Err: Debug,
+{
+    type Err = NestedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<T> Nested<T> {
+    pub fn is_leaf(&self) -> bool {
+        matches!(self, Nested::Leaf(_))
+    }
+
+    pub fn as_leaf(&self) -> Option<&T> {
+        match self {
+            Nested::Leaf(v) => Some(v),
+            Nested::List(_) => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Nested<T>]> {
+        match self {
+            Nested::Leaf(_) => None,
+            Nested::List(list) => Some(list),
+        }
+    }
+
+    /// How deeply lists are nested. A leaf has depth 0, `[]` and `[1]` have
+    /// depth 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Nested::Leaf(_) => 0,
+            Nested::List(list) => 1 + list.iter().map(Self::depth).max().unwrap_or(0),
+        }
+    }
+
+    /// All leaves from left to right, ignoring the structure.
+    pub fn leaves(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self {
+            Nested::Leaf(v) => Box::new(std::iter::once(v)),
+            Nested::List(list) => Box::new(list.iter().flat_map(Self::leaves)),
+        }
+    }
+}
+
+impl<T: Ord> Nested<T> {
+    /// Compare two trees, treating a leaf compared to a list as if the leaf
+    /// was a list containing just that leaf. Lists compare element by element,
+    /// and a list that runs out first is lesser.
+    ///
+    /// This isn't used for `Ord` since it would disagree with `Eq`, eg `1`
+    /// and `[1]` are ordered equally.
+    pub fn cmp_promoting(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Nested::Leaf(l), Nested::Leaf(r)) => l.cmp(r),
+            (Nested::List(l), Nested::List(r)) => Self::cmp_lists(l, r),
+            (Nested::Leaf(_), Nested::List(r)) => Self::cmp_lists(std::slice::from_ref(self), r),
+            (Nested::List(l), Nested::Leaf(_)) => Self::cmp_lists(l, std::slice::from_ref(other)),
+        }
+    }
+
+    fn cmp_lists(left: &[Self], right: &[Self]) -> Ordering {
+        for (l, r) in left.iter().zip(right) {
+            match l.cmp_promoting(r) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+        left.len().cmp(&right.len())
+    }
+}
+
+impl<T: Display> Nested<T> {
+    /// Display with the given delimiters, so something parsed with
+    /// `parse_with` prints the same way it was written.
+    pub fn display_with(&self, delims: Delims) -> DisplayWith<'_, T> {
+        DisplayWith {
+            nested: self,
+            delims,
+        }
+    }
+}
+
+/// Always uses the default delimiters, see `Nested::display_with` for others.
+impl<T: Display> Display for Nested<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display_with(Delims::default()).fmt(f)
+    }
+}
+
+/// A `Nested` with the delimiters to display it with.
+pub struct DisplayWith<'a, T> {
+    nested: &'a Nested<T>,
+    delims: Delims,
+}
+
+impl<T: Display> Display for DisplayWith<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.nested {
+            Nested::Leaf(v) => write!(f, "{v}"),
+            Nested::List(list) => {
+                write!(f, "{}", self.delims.open)?;
+                for (i, v) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", self.delims.sep)?;
+                    }
+                    write!(f, "{}", v.display_with(self.delims))?;
+                }
+                write!(f, "{}", self.delims.close)
+            }
+        }
+    }
+}
+
+/// Simple recursive descent parser over the characters of the input.
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    delims: Delims,
+}
+
+impl Parser<'_> {
+    fn value<T: FromStr>(&mut self) -> Result<Nested<T>, NestedError>
+    where
+        T::Err: Debug,
+    {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some((_, c)) if c == self.delims.open => {
+                self.chars.next();
+                self.list()
+            }
+            Some((pos, c)) if c == self.delims.close || c == self.delims.sep => {
+                Err(NestedError::Unexpected {
+                    pos,
+                    found: Some(c),
+                })
+            }
+            Some((pos, _)) => self.leaf(pos),
+            None => Err(NestedError::Unexpected {
+                pos: self.input.len(),
+                found: None,
+            }),
+        }
+    }
+
+    // Already consumed the opening delimiter.
+    fn list<T: FromStr>(&mut self) -> Result<Nested<T>, NestedError>
+    where
+        T::Err: Debug,
+    {
+        let mut list = vec![];
+
+        self.skip_whitespace();
+        if let Some((_, c)) = self.chars.peek()
+            && *c == self.delims.close
+        {
+            self.chars.next();
+            return Ok(Nested::List(list));
+        }
+
+        loop {
+            list.push(self.value()?);
+            let spaced = self.skip_whitespace();
+            match self.chars.peek().copied() {
+                Some((_, c)) if c == self.delims.close => {
+                    self.chars.next();
+                    return Ok(Nested::List(list));
+                }
+                Some((_, c)) if c == self.delims.sep => {
+                    self.chars.next();
+                }
+                // The whitespace just skipped was the separator.
+                Some(_) if spaced && self.delims.sep.is_whitespace() => {}
+                found => {
+                    return Err(NestedError::Unexpected {
+                        pos: found.map_or(self.input.len(), |(pos, _)| pos),
+                        found: found.map(|(_, c)| c),
+                    });
+                }
+            }
+        }
+    }
+
+    fn leaf<T: FromStr>(&mut self, start: usize) -> Result<Nested<T>, NestedError>
+    where
+        T::Err: Debug,
+    {
+        let mut end = start;
+        while let Some((pos, c)) = self.chars.peek().copied() {
+            if c == self.delims.open
+                || c == self.delims.close
+                || c == self.delims.sep
+                || (c.is_whitespace() && self.delims.sep.is_whitespace())
+            {
+                break;
+            }
+            self.chars.next();
+            end = pos + c.len_utf8();
+        }
+
+        let token = self.input[start..end].trim_end();
+        token
+            .parse()
+            .map(Nested::Leaf)
+            .map_err(|e| NestedError::Leaf {
+                pos: start,
+                token: token.to_string(),
+                message: format!("{e:?}"),
+            })
+    }
+
+    // Returns whether any was skipped. A whitespace separator is skipped too,
+    // and `list` works out whether it was needed.
+    fn skip_whitespace(&mut self) -> bool {
+        let sep = self.delims.sep;
+        let mut skipped = false;
+        while self
+            .chars
+            .next_if(|(_, c)| c.is_whitespace() && (*c != sep || sep.is_whitespace()))
+            .is_some()
+        {
+            skipped = true;
+        }
+        skipped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use crate::{Delims, Nested, NestedError};
+
+    fn n(s: &str) -> Nested<i64> {
+        Nested::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        use Nested::*;
+        assert_eq!(n("[]"), List(vec![]));
+        assert_eq!(n(" 12 "), Leaf(12));
+        assert_eq!(
+            n("[[1], [2,[3]]]"),
+            List(vec![
+                List(vec![Leaf(1)]),
+                List(vec![Leaf(2), List(vec![Leaf(3)])])
+            ])
+        );
+        assert_eq!(n("[[1],[2,[3]]]").to_string(), "[[1],[2,[3]]]");
+        assert_eq!(n("[[[]],4]").depth(), 3);
+        assert_eq!(
+            n("[1,[2,[3,[4]]],5]").leaves().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+
+        let delims = Delims {
+            open: '(',
+            close: ')',
+            sep: ' ',
+        };
+        let parsed = Nested::<i64>::parse_with("(1 (2 3))", delims).unwrap();
+        assert_eq!(parsed, n("[1,[2,3]]"));
+        assert_eq!(parsed.display_with(delims).to_string(), "(1 (2 3))");
+        for spaced in ["(1  2)", "(1 2 )", "( 1 2)", "(1\t 2)"] {
+            assert_eq!(
+                Nested::<i64>::parse_with(spaced, delims).unwrap(),
+                n("[1,2]"),
+                "{spaced:?}"
+            );
+        }
+        assert!(Nested::<i64>::parse_with("(1 2", delims).is_err());
+        // Plain Display ignores how it was parsed.
+        assert_eq!(parsed.to_string(), "[1,[2,3]]");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Nested::<i64>::parse("[1,2"),
+            Err(NestedError::Unexpected {
+                pos: 4,
+                found: None
+            })
+        );
+        assert_eq!(
+            Nested::<i64>::parse("[1,,2]"),
+            Err(NestedError::Unexpected {
+                pos: 3,
+                found: Some(',')
+            })
+        );
+        assert_eq!(
+            Nested::<i64>::parse("[1]]"),
+            Err(NestedError::Unexpected {
+                pos: 3,
+                found: Some(']')
+            })
+        );
+        assert!(matches!(
+            Nested::<i64>::parse("[1,x]"),
+            Err(NestedError::Leaf { pos: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn ordering() {
+        let cmp = |l, r| n(l).cmp_promoting(&n(r));
+        assert_eq!(cmp("[1,1,3,1,1]", "[1,1,5,1,1]"), Ordering::Less);
+        assert_eq!(cmp("[[1],[2,3,4]]", "[[1],4]"), Ordering::Less);
+        assert_eq!(cmp("[9]", "[[8,7,6]]"), Ordering::Greater);
+        assert_eq!(cmp("[[4,4],4,4]", "[[4,4],4,4,4]"), Ordering::Less);
+        assert_eq!(cmp("[7,7,7,7]", "[7,7,7]"), Ordering::Greater);
+        assert_eq!(cmp("[]", "[3]"), Ordering::Less);
+        assert_eq!(cmp("[[[]]]", "[[]]"), Ordering::Greater);
+        assert_eq!(cmp("[1]", "1"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,229 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// A min priority queue, which the searches in `search` can run on. There's
+/// no decrease key, instead push the item again with its lower priority and
+/// skip the stale copy when it comes out later.
+pub trait PriorityQueue<P, T> {
+    fn push(&mut self, priority: P, item: T);
+
+    /// Take out an item with the lowest priority.
+    fn pop(&mut self) -> Option<(P, T)>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The usual heap, with the order flipped so the lowest priority comes out
+/// first. Ties come out by the smallest item.
+impl<P: Ord, T: Ord> PriorityQueue<P, T> for BinaryHeap<Reverse<(P, T)>> {
+    fn push(&mut self, priority: P, item: T) {
+        BinaryHeap::push(self, Reverse((priority, item)));
+    }
+
+    fn pop(&mut self) -> Option<(P, T)> {
+        BinaryHeap::pop(self).map(|Reverse(x)| x)
+    }
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+}
+
+/// A list of items for each priority. Pushing and popping are constant time,
+/// apart from walking past empty buckets, so it suits small integer costs
+/// like on grids. Uses memory in proportion to the biggest priority pushed.
+#[derive(Debug, Clone)]
+pub struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    // Every bucket before this is empty.
+    lowest: usize,
+    len: usize,
+}
+
+impl<T> BucketQueue<T> {
+    pub fn new() -> Self {
+        BucketQueue {
+            buckets: vec![],
+            lowest: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T> Default for BucketQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PriorityQueue<usize, T> for BucketQueue<T> {
+    fn push(&mut self, priority: usize, item: T) {
+        if priority >= self.buckets.len() {
+            self.buckets.resize_with(priority + 1, Vec::new);
+        }
+        self.buckets[priority].push(item);
+        self.lowest = self.lowest.min(priority);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(usize, T)> {
+        while self.lowest < self.buckets.len() {
+            if let Some(item) = self.buckets[self.lowest].pop() {
+                self.len -= 1;
+                return Some((self.lowest, item));
+            }
+            self.lowest += 1;
+        }
+        None
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A monotone heap, where nothing pushed can be lower than the last thing
+/// popped. That always holds for Dijkstra, and for A* with a heuristic that
+/// never drops by more than the cost of a step. Unlike `BucketQueue` the
+/// priorities can be as big as they like.
+#[derive(Debug, Clone)]
+pub struct RadixHeap<T> {
+    // Bucket i holds priorities whose highest bit that differs from `last`
+    // is bit i - 1, and bucket 0 those equal to `last`.
+    buckets: Vec<Vec<(usize, T)>>,
+    last: usize,
+    len: usize,
+}
+
+impl<T> RadixHeap<T> {
+    pub fn new() -> Self {
+        RadixHeap {
+            buckets: (0..=usize::BITS).map(|_| vec![]).collect(),
+            last: 0,
+            len: 0,
+        }
+    }
+
+    fn bucket(&self, priority: usize) -> usize {
+        (usize::BITS - (priority ^ self.last).leading_zeros()) as usize
+    }
+}
+
+impl<T> Default for RadixHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PriorityQueue<usize, T> for RadixHeap<T> {
+    fn push(&mut self, priority: usize, item: T) {
+        assert!(
+            priority >= self.last,
+            "pushed {priority} after popping {}",
+            self.last
+        );
+        let b = self.bucket(priority);
+        self.buckets[b].push((priority, item));
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(usize, T)> {
+        if self.buckets[0].is_empty() {
+            // Everything in the first non-empty bucket moves to lower
+            // buckets once its smallest priority is the new `last`.
+            let b = self.buckets.iter().position(|b| !b.is_empty())?;
+            let items = std::mem::take(&mut self.buckets[b]);
+            self.last = items.iter().map(|(p, _)| *p).min().unwrap();
+            for (p, item) in items {
+                let nb = self.bucket(p);
+                self.buckets[nb].push((p, item));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cmp::Reverse, collections::BinaryHeap};
+
+    use crate::queue::{BucketQueue, PriorityQueue, RadixHeap};
+
+    fn drain(q: &mut impl PriorityQueue<usize, char>) -> Vec<usize> {
+        let mut out = vec![];
+        while let Some((p, _)) = q.pop() {
+            out.push(p);
+        }
+        out
+    }
+
+    fn fill(q: &mut impl PriorityQueue<usize, char>) {
+        for (p, c) in [(5, 'a'), (1, 'b'), (9, 'c'), (1, 'd'), (0, 'e'), (300, 'f')] {
+            q.push(p, c);
+        }
+    }
+
+    #[test]
+    fn in_order() {
+        let want = vec![0, 1, 1, 5, 9, 300];
+
+        let mut heap = BinaryHeap::<Reverse<(usize, char)>>::new();
+        fill(&mut heap);
+        assert_eq!(drain(&mut heap), want);
+
+        let mut buckets = BucketQueue::new();
+        fill(&mut buckets);
+        assert_eq!(buckets.len(), 6);
+        assert_eq!(drain(&mut buckets), want);
+        assert!(buckets.is_empty());
+
+        let mut radix = RadixHeap::new();
+        fill(&mut radix);
+        assert_eq!(drain(&mut radix), want);
+        assert!(radix.is_empty());
+    }
+
+    #[test]
+    fn interleaved() {
+        // Pushes in between pops, never below the last pop.
+        let mut buckets = BucketQueue::new();
+        let mut radix = RadixHeap::new();
+        let mut popped = vec![];
+        let mut x = 7usize;
+        for i in 0..200 {
+            let last = popped.last().copied().unwrap_or(0);
+            x = (x * 31 + 11) % 97;
+            buckets.push(last + x % 10, i);
+            radix.push(last + x % 10, i);
+            if i % 3 == 0 {
+                let (a, _) = buckets.pop().unwrap();
+                let (b, _) = radix.pop().unwrap();
+                assert_eq!(a, b);
+                popped.push(a);
+            }
+        }
+        assert!(popped.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(buckets.len(), radix.len());
+
+        // Buckets can go back down, unlike the radix heap.
+        buckets.push(0, 0);
+        assert_eq!(buckets.pop(), Some((0, 0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_not_monotone() {
+        let mut radix = RadixHeap::new();
+        radix.push(10, 'a');
+        radix.pop();
+        radix.push(9, 'b');
+    }
+}
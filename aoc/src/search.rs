@@ -0,0 +1,623 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use crate::queue::PriorityQueue;
+
+/// The cheapest way found from the start to a goal, including both ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    pub states: Vec<S>,
+}
+
+/// Every state seen so far, numbered in the order they were found. Searches
+/// work with the numbers so that states are only cloned once.
+struct Seen<S> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    parent: Vec<usize>,
+}
+
+impl<S: Clone + Hash + Eq> Seen<S> {
+    fn new(start: S) -> Self {
+        Seen {
+            states: vec![start.clone()],
+            index: HashMap::from([(start, 0)]),
+            parent: vec![usize::MAX],
+        }
+    }
+
+    /// Number for a state, and whether it's new.
+    fn insert(&mut self, state: S, parent: usize) -> (usize, bool) {
+        if let Some(&i) = self.index.get(&state) {
+            return (i, false);
+        }
+        let i = self.states.len();
+        self.states.push(state.clone());
+        self.index.insert(state, i);
+        self.parent.push(parent);
+        (i, true)
+    }
+
+    fn path<C>(&self, mut i: usize, cost: C) -> Path<S, C> {
+        let mut states = vec![];
+        while i != usize::MAX {
+            states.push(self.states[i].clone());
+            i = self.parent[i];
+        }
+        states.reverse();
+        Path { cost, states }
+    }
+}
+
+/// Every cheapest path from the start to a goal at once. Each state keeps all
+/// the states it can be reached from at its best cost, which makes a DAG
+/// rooted at the start.
+#[derive(Debug, Clone)]
+pub struct PathDag<S, C> {
+    pub cost: C,
+    states: Vec<S>,
+    preds: Vec<Vec<usize>>,
+    ends: Vec<usize>,
+}
+
+impl<S: Clone + Hash + Eq, C> PathDag<S, C> {
+    /// Goal states reached at the best cost.
+    pub fn ends(&self) -> impl Iterator<Item = &S> {
+        self.ends.iter().map(|&i| &self.states[i])
+    }
+
+    /// Number of distinct cheapest paths.
+    pub fn count_paths(&self) -> u128 {
+        let mut ways = vec![None; self.states.len()];
+        let mut total = 0;
+        for &end in &self.ends {
+            // Walk back until every predecessor has a count, then fill in.
+            let mut stack = vec![end];
+            while let Some(&i) = stack.last() {
+                if ways[i].is_some() {
+                    stack.pop();
+                    continue;
+                }
+                let missing = self.preds[i]
+                    .iter()
+                    .filter(|&&p| ways[p].is_none())
+                    .copied()
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    ways[i] = Some(if self.preds[i].is_empty() {
+                        1
+                    } else {
+                        self.preds[i].iter().map(|&p| ways[p].unwrap()).sum()
+                    });
+                    stack.pop();
+                } else {
+                    stack.extend(missing);
+                }
+            }
+            total += ways[end].unwrap();
+        }
+        total
+    }
+
+    /// Each cheapest path in turn, from the start to a goal. They're worked
+    /// out as they're asked for, so it's fine to `take` a few when there are
+    /// loads of them.
+    pub fn paths(&self) -> Paths<'_, S, C> {
+        Paths {
+            dag: self,
+            stack: vec![],
+            next_end: 0,
+        }
+    }
+
+    /// Every state that's on at least one cheapest path.
+    pub fn states(&self) -> HashSet<S> {
+        self.points(|s| s.clone())
+    }
+
+    /// Like `states` but keyed, for when the states carry more than where
+    /// they are, eg a position and a facing.
+    pub fn points<K: Hash + Eq>(&self, mut key: impl FnMut(&S) -> K) -> HashSet<K> {
+        let mut on_path = vec![false; self.states.len()];
+        let mut stack = self.ends.clone();
+        while let Some(i) = stack.pop() {
+            if !on_path[i] {
+                on_path[i] = true;
+                stack.extend(&self.preds[i]);
+            }
+        }
+        on_path
+            .iter()
+            .zip(&self.states)
+            .filter(|(on, _)| **on)
+            .map(|(_, s)| key(s))
+            .collect()
+    }
+}
+
+/// Iterator over the cheapest paths in a `PathDag`.
+pub struct Paths<'a, S, C> {
+    dag: &'a PathDag<S, C>,
+    // From a goal back towards the start, with which predecessor is being
+    // followed at each state.
+    stack: Vec<(usize, usize)>,
+    next_end: usize,
+}
+
+impl<S: Clone, C> Iterator for Paths<'_, S, C> {
+    type Item = Vec<S>;
+
+    fn next(&mut self) -> Option<Vec<S>> {
+        if self.stack.is_empty() {
+            let &end = self.dag.ends.get(self.next_end)?;
+            self.next_end += 1;
+            self.stack.push((end, 0));
+        }
+
+        // Only the start has no predecessors.
+        while let Some(&p) = self
+            .stack
+            .last()
+            .and_then(|&(i, k)| self.dag.preds[i].get(k))
+        {
+            self.stack.push((p, 0));
+        }
+        let path = self
+            .stack
+            .iter()
+            .rev()
+            .map(|&(i, _)| self.dag.states[i].clone())
+            .collect();
+
+        // Move on to the next predecessor that hasn't been tried.
+        self.stack.pop();
+        while let Some((i, k)) = self.stack.last_mut() {
+            *k += 1;
+            if *k < self.dag.preds[*i].len() {
+                break;
+            }
+            self.stack.pop();
+        }
+        Some(path)
+    }
+}
+
+/// Breadth first search where every step costs one. Neighbours are just the
+/// states reachable in one step. The cost of the path is the number of
+/// steps.
+pub fn bfs<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = Seen::new(start);
+    let mut q = VecDeque::from([(0, 0)]);
+    while let Some((i, steps)) = q.pop_front() {
+        if goal(&seen.states[i]) {
+            return Some(seen.path(i, steps));
+        }
+        for next in neighbours(&seen.states[i]) {
+            let (n, new) = seen.insert(next, i);
+            if new {
+                q.push_back((n, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path where neighbours come with the cost of stepping to them,
+/// which can't be negative. Costs start from `C::default()`, so any of the
+/// integer types work.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, neighbours, goal, |_| C::default())
+}
+
+/// Like `dijkstra` but heads towards the goal first using a heuristic, which
+/// estimates the remaining cost from a state. It needs to never overestimate,
+/// eg the taxicab distance to the goal when every step costs at least one.
+/// States are looked at again if a cheaper way to them turns up later, which
+/// only happens when the heuristic is inconsistent, ie it can drop by more
+/// than the cost of a step: `h(a) > step(a, b) + h(b)`.
+pub fn astar<S, C, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+    heuristic: impl FnMut(&S) -> C,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar_with(BinaryHeap::new(), start, neighbours, goal, heuristic)
+}
+
+/// Like `dijkstra` but using the given queue, eg a `BucketQueue` when the
+/// costs are small.
+pub fn dijkstra_with<S, C, I, Q>(
+    queue: Q,
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+    Q: PriorityQueue<C, (C, usize)>,
+{
+    astar_with(queue, start, neighbours, goal, |_| C::default())
+}
+
+/// Like `astar` but using the given queue. It only needs to hand back the
+/// lowest estimates first. A `RadixHeap` also needs the heuristic to be
+/// consistent, so that nothing is pushed below what's already come out.
+pub fn astar_with<S, C, I, Q>(
+    mut queue: Q,
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+    mut heuristic: impl FnMut(&S) -> C,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+    Q: PriorityQueue<C, (C, usize)>,
+{
+    let mut seen = Seen::new(start);
+    let mut best = vec![C::default()];
+    let mut done = vec![false];
+
+    // Ordered by estimated total cost, with the cost so far alongside.
+    queue.push(heuristic(&seen.states[0]), (C::default(), 0));
+
+    while let Some((_, (cost, i))) = queue.pop() {
+        if done[i] {
+            continue;
+        }
+        done[i] = true;
+
+        if goal(&seen.states[i]) {
+            return Some(seen.path(i, cost));
+        }
+
+        for (next, step) in neighbours(&seen.states[i]) {
+            let next_cost = cost + step;
+            let (n, new) = seen.insert(next, i);
+            if new {
+                best.push(next_cost);
+                done.push(false);
+            } else if next_cost >= best[n] {
+                continue;
+            } else {
+                best[n] = next_cost;
+                seen.parent[n] = i;
+                done[n] = false;
+            }
+            queue.push(next_cost + heuristic(&seen.states[n]), (next_cost, n));
+        }
+    }
+    None
+}
+
+/// Like `dijkstra` but finds every cheapest path rather than just one. Steps
+/// should cost more than zero, or there can be loops with no cost.
+pub fn dijkstra_all<S, C, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Option<PathDag<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar_all(start, neighbours, goal, |_| C::default())
+}
+
+/// Like `astar` but finds every cheapest path rather than just one. It keeps
+/// going after the first goal until nothing left could tie with it. The same
+/// goes for the heuristic, it only needs to never overestimate.
+pub fn astar_all<S, C, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+    mut heuristic: impl FnMut(&S) -> C,
+) -> Option<PathDag<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut seen = Seen::new(start);
+    let mut best = vec![C::default()];
+    let mut done = vec![false];
+    let mut preds = vec![vec![]];
+    let mut ends = vec![];
+    let mut goal_cost = None;
+
+    let mut q = BinaryHeap::new();
+    q.push(Reverse((heuristic(&seen.states[0]), C::default(), 0)));
+
+    while let Some(Reverse((estimate, cost, i))) = q.pop() {
+        if goal_cost.is_some_and(|g| estimate > g) {
+            break;
+        }
+        if done[i] {
+            continue;
+        }
+        done[i] = true;
+
+        if goal(&seen.states[i]) {
+            goal_cost = Some(cost);
+            ends.push(i);
+            continue;
+        }
+
+        for (next, step) in neighbours(&seen.states[i]) {
+            let next_cost = cost + step;
+            let (n, new) = seen.insert(next, i);
+            if new {
+                best.push(next_cost);
+                done.push(false);
+                preds.push(vec![i]);
+            } else if next_cost == best[n] {
+                preds[n].push(i);
+                continue;
+            } else if next_cost > best[n] {
+                continue;
+            } else {
+                best[n] = next_cost;
+                preds[n] = vec![i];
+                done[n] = false;
+            }
+            q.push(Reverse((
+                next_cost + heuristic(&seen.states[n]),
+                next_cost,
+                n,
+            )));
+        }
+    }
+
+    Some(PathDag {
+        cost: goal_cost?,
+        states: seen.states,
+        preds,
+        ends,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        queue::{BucketQueue, RadixHeap},
+        search::{astar, astar_all, astar_with, bfs, dijkstra, dijkstra_all, dijkstra_with},
+        two::{DenseField, Dirn, IPoint, pt},
+    };
+
+    fn maze() -> DenseField<u8> {
+        DenseField::from_lines(
+            [
+                "S.#......",
+                ".##.####.",
+                "....#..#.",
+                "##.##.##.",
+                "......#.E",
+            ]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn bfs_maze() {
+        let field = maze();
+        let end = field.find(&b'E').unwrap();
+        let open = |p: &IPoint| {
+            field
+                .neighbours4_bounded(*p)
+                .filter(|(c, _)| **c != b'#')
+                .map(|(_, n)| n)
+                .collect::<Vec<_>>()
+        };
+
+        let path = bfs(pt(0, 0), open, |p| *p == end).unwrap();
+        assert_eq!(path.cost, 16);
+        assert_eq!(path.states.len(), 17);
+        assert_eq!(path.states[0], pt(0, 0));
+        assert_eq!(*path.states.last().unwrap(), end);
+        for w in path.states.windows(2) {
+            assert_eq!(w[0].taxicab_dist(w[1]), 1);
+        }
+
+        assert_eq!(bfs(pt(0, 0), open, |p| *p == pt(2, 0)), None);
+    }
+
+    #[test]
+    fn weighted() {
+        // Digits are the cost of entering a cell, like 2023 day 17 without
+        // the turning rules.
+        let field = DenseField::<u8>::from_lines(
+            ["1163751", "1381373", "2136511", "3694931", "7463417"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        );
+        let end = pt(field.width() - 1, field.height() - 1);
+        let neighbours = |p: &IPoint| {
+            field
+                .neighbours4_bounded(*p)
+                .map(|(c, n)| (n, (c - b'0') as u32))
+                .collect::<Vec<_>>()
+        };
+
+        let by_dijkstra = dijkstra(pt(0, 0), neighbours, |p| *p == end).unwrap();
+        let by_astar = astar(
+            pt(0, 0),
+            neighbours,
+            |p| *p == end,
+            |p| p.taxicab_dist(end) as u32,
+        )
+        .unwrap();
+        assert_eq!(by_dijkstra.cost, 28);
+        assert_eq!(by_astar.cost, 28);
+
+        // The path's cost adds up.
+        let total: u32 = by_astar.states[1..]
+            .iter()
+            .map(|p| (field.get(*p) - b'0') as u32)
+            .sum();
+        assert_eq!(total, 28);
+    }
+
+    #[test]
+    fn weighted_queues() {
+        let field = DenseField::<u8>::from_lines(
+            ["1163751", "1381373", "2136511", "3694931", "7463417"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        );
+        let end = pt(field.width() - 1, field.height() - 1);
+        let neighbours = |p: &IPoint| {
+            field
+                .neighbours4_bounded(*p)
+                .map(|(c, n)| (n, (c - b'0') as usize))
+                .collect::<Vec<_>>()
+        };
+
+        let by_buckets = dijkstra_with(BucketQueue::new(), pt(0, 0), neighbours, |p| *p == end);
+        let by_radix = astar_with(
+            RadixHeap::new(),
+            pt(0, 0),
+            neighbours,
+            |p| *p == end,
+            |p| p.taxicab_dist(end),
+        );
+        assert_eq!(by_buckets.unwrap().cost, 28);
+        assert_eq!(by_radix.unwrap().cost, 28);
+    }
+
+    #[test]
+    fn all_paths_grid() {
+        // Every monotone path across an open 3x3 grid, C(4, 2) of them.
+        let neighbours = |p: &IPoint| {
+            [pt(p.x + 1, p.y), pt(p.x, p.y + 1)]
+                .into_iter()
+                .filter(|n| n.x < 3 && n.y < 3)
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let dag = dijkstra_all(pt(0, 0), neighbours, |p| *p == pt(2, 2)).unwrap();
+        assert_eq!(dag.cost, 4);
+        assert_eq!(dag.count_paths(), 6);
+        assert_eq!(dag.states().len(), 9);
+
+        let paths = dag.paths().collect::<Vec<_>>();
+        assert_eq!(paths.len(), 6);
+        for path in &paths {
+            assert_eq!(path.len(), 5);
+            assert_eq!(path[0], pt(0, 0));
+            assert_eq!(path[4], pt(2, 2));
+        }
+        let distinct = paths.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(distinct.len(), 6);
+
+        assert!(dijkstra_all(pt(0, 0), neighbours, |p| *p == pt(5, 5)).is_none());
+    }
+
+    #[test]
+    fn all_paths_reindeer() {
+        // 2024 day 16: moving costs 1 and turning costs 1000.
+        let field = DenseField::<u8>::from_lines(
+            [
+                "###############",
+                "#.......#....E#",
+                "#.#.###.#.###.#",
+                "#.....#.#...#.#",
+                "#.###.#####.#.#",
+                "#.#.#.......#.#",
+                "#.#.#####.###.#",
+                "#...........#.#",
+                "###.#.#####.#.#",
+                "#...#.....#.#.#",
+                "#.#.#.###.#.#.#",
+                "#.....#...#.#.#",
+                "#.###.#.#.#.#.#",
+                "#S..#.....#...#",
+                "###############",
+            ]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        );
+        let start = field.find(&b'S').unwrap();
+        let end = field.find(&b'E').unwrap();
+        let neighbours = |&(p, d): &(IPoint, Dirn)| {
+            let mut next = vec![((p, d.clockwise()), 1000), ((p, d.anticlockwise()), 1000)];
+            if *field.get(p + d.as_point()) != b'#' {
+                next.push(((p + d.as_point(), d), 1));
+            }
+            next
+        };
+
+        let dag = astar_all(
+            (start, Dirn::Right),
+            neighbours,
+            |(p, _)| *p == end,
+            |(p, _)| p.taxicab_dist(end),
+        )
+        .unwrap();
+        assert_eq!(dag.cost, 7036);
+        assert_eq!(dag.points(|(p, _)| *p).len(), 45);
+        assert_eq!(dag.count_paths(), dag.paths().count() as u128);
+    }
+
+    #[test]
+    fn inconsistent_heuristic() {
+        // The heuristic never overestimates but drops by 4 going from b to a,
+        // so a is first reached the expensive way and has to be looked at
+        // again once the cheap way through b turns up.
+        let neighbours = |s: &char| match s {
+            's' => vec![('a', 4), ('b', 1)],
+            'b' => vec![('a', 1)],
+            'a' => vec![('g', 5)],
+            _ => vec![],
+        };
+        let heuristic = |s: &char| match s {
+            'a' => 1,
+            'b' => 5,
+            _ => 0,
+        };
+
+        let path = astar('s', neighbours, |s| *s == 'g', heuristic).unwrap();
+        assert_eq!(path.cost, 7);
+        assert_eq!(path.states, vec!['s', 'b', 'a', 'g']);
+
+        let dag = astar_all('s', neighbours, |s| *s == 'g', heuristic).unwrap();
+        assert_eq!(dag.cost, 7);
+        assert_eq!(dag.count_paths(), 1);
+        assert_eq!(dag.paths().next(), Some(vec!['s', 'b', 'a', 'g']));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    path::Path,
+    str::FromStr,
+};
+
+use crate::line_blocks;
+
+/// Something that can parse a single block of lines from the input. Closures
+/// and functions taking `&[String]` implement this, as do `parse` and
+/// `parse_lines` for types implementing FromStr. A plain closure has no way to
+/// report a bad section other than panicking, so wrap it in `fallible` if it
+/// returns a Result instead.
+pub trait SectionParser {
+    type Output;
+
+    fn parse_section(&self, block: &[String]) -> Result<Self::Output, String>;
+}
+
+impl<F, T> SectionParser for F
+where
+    F: Fn(&[String]) -> T,
+{
+    type Output = T;
+
+    fn parse_section(&self, block: &[String]) -> Result<T, String> {
+        Ok(self(block))
+    }
+}
+
+/// Parser from a closure that can fail, see `fallible`.
+pub struct Fallible<F>(F);
+
+/// Use a closure returning a Result as a parser, so that its errors come back
+/// as `SectionsError::Parse` like the FromStr parsers.
+pub fn fallible<F, T, E>(f: F) -> Fallible<F>
+where
+    F: Fn(&[String]) -> Result<T, E>,
+    E: Debug,
+{
+    Fallible(f)
+}
+
+impl<F, T, E> SectionParser for Fallible<F>
+where
+    F: Fn(&[String]) -> Result<T, E>,
+    E: Debug,
+{
+    type Output = T;
+
+    fn parse_section(&self, block: &[String]) -> Result<T, String> {
+        (self.0)(block).map_err(|e| format!("{e:?}"))
+    }
+}
+
+/// Parser for a whole block as a single FromStr type, with the lines joined
+/// back together by newlines.
+pub struct Parse<T>(PhantomData<T>);
+
+pub fn parse<T>() -> Parse<T> {
+    Parse(PhantomData)
+}
+
+impl<T: FromStr> SectionParser for Parse<T>
+where
+    T::Err: Debug,
+{
+    type Output = T;
+
+    fn parse_section(&self, block: &[String]) -> Result<T, String> {
+        block.join("\n").parse().map_err(|e| format!("{e:?}"))
+    }
+}
+
+/// Parser for a block where each line is parsed as a FromStr type.
+pub struct ParseLines<T>(PhantomData<T>);
+
+pub fn parse_lines<T>() -> ParseLines<T> {
+    ParseLines(PhantomData)
+}
+
+impl<T: FromStr> SectionParser for ParseLines<T>
+where
+    T::Err: Debug,
+{
+    type Output = Vec<T>;
+
+    fn parse_section(&self, block: &[String]) -> Result<Vec<T>, String> {
+        block
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                line.parse()
+                    .map_err(|e| format!("line {i} '{line}': {e:?}"))
+            })
+            .collect()
+    }
+}
+
+/// A tuple of section parsers, one for each block of the input.
+pub trait Sections {
+    type Output;
+    const COUNT: usize;
+
+    fn parse_sections(&self, blocks: &[Vec<String>]) -> Result<Self::Output, SectionsError>;
+}
+
+macro_rules! impl_sections {
+    ($count:literal; $($parser:ident $i:tt),+) => {
+        impl<$($parser: SectionParser),+> Sections for ($($parser,)+) {
+            type Output = ($($parser::Output,)+);
+            const COUNT: usize = $count;
+
+            fn parse_sections(
+                &self,
+                blocks: &[Vec<String>],
+            ) -> Result<Self::Output, SectionsError> {
+                if blocks.len() != Self::COUNT {
+                    return Err(SectionsError::Count {
+                        expected: Self::COUNT,
+                        found: blocks.len(),
+                    });
+                }
+
+                Ok(($(
+                    self.$i
+                        .parse_section(&blocks[$i])
+                        .map_err(|message| SectionsError::Parse { section: $i, message })?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_sections!(1; A 0);
+impl_sections!(2; A 0, B 1);
+impl_sections!(3; A 0, B 1, C 2);
+impl_sections!(4; A 0, B 1, C 2, D 3);
+impl_sections!(5; A 0, B 1, C 2, D 3, E 4);
+impl_sections!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionsError {
+    /// The input had a different number of blank line separated blocks to the
+    /// number of parsers given.
+    Count { expected: usize, found: usize },
+    /// A parser failed on the given section, counting from zero.
+    Parse { section: usize, message: String },
+}
+
+impl Display for SectionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionsError::Count { expected, found } => write!(
+                f,
+                "expected {expected} blank line separated sections, found {found}"
+            ),
+            SectionsError::Parse { section, message } => {
+                write!(f, "failed to parse section {section}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for SectionsError {}
+
+/// Parse each block of the input with its own parser, giving a tuple of the
+/// results, eg `let (rules, updates) = sections(path, (parse_rules, parse_updates))`.
+/// Lives at `aoc::sections::sections` so it doesn't clash with a day's own
+/// functions.
+/// Panics if the number of blocks is wrong or any parser fails.
+pub fn sections<S: Sections>(path: impl AsRef<Path>, parsers: S) -> S::Output {
+    try_sections(&line_blocks(path), parsers).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Same as `sections` but on blocks already split, returning any error.
+pub fn try_sections<S: Sections>(
+    blocks: &[Vec<String>],
+    parsers: S,
+) -> Result<S::Output, SectionsError> {
+    parsers.parse_sections(blocks)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        line_blocks_from_str,
+        sections::{SectionsError, fallible, parse, parse_lines, try_sections},
+    };
+
+    #[test]
+    fn tuple() {
+        let blocks = line_blocks_from_str("a: 1\nb: 2\n\n1\n2\n3\n\n42");
+        let regs = |b: &[String]| b.len();
+        let (regs, nums, answer) =
+            try_sections(&blocks, (regs, parse_lines::<u8>(), parse::<u32>())).unwrap();
+
+        assert_eq!(regs, 2);
+        assert_eq!(nums, vec![1, 2, 3]);
+        assert_eq!(answer, 42);
+    }
+
+    #[test]
+    fn errors() {
+        let blocks = line_blocks_from_str("1\n\n2");
+        assert_eq!(
+            try_sections(&blocks, (parse::<u8>(),)),
+            Err(SectionsError::Count {
+                expected: 1,
+                found: 2
+            })
+        );
+
+        let blocks = line_blocks_from_str("1\n\n2\nx");
+        let err = try_sections(&blocks, (parse::<u8>(), parse_lines::<u8>())).unwrap_err();
+        assert!(matches!(err, SectionsError::Parse { section: 1, .. }));
+        assert!(
+            err.to_string()
+                .starts_with("failed to parse section 1: line 1 'x'")
+        );
+
+        let blocks = line_blocks_from_str("a: 1\n\nb 2");
+        let reg = |b: &[String]| {
+            b[0].split_once(": ")
+                .map(|(_, v)| v.to_string())
+                .ok_or("no colon")
+        };
+        assert_eq!(
+            try_sections(&blocks, (fallible(reg), fallible(reg))),
+            Err(SectionsError::Parse {
+                section: 1,
+                message: "\"no colon\"".to_string()
+            })
+        );
+        let blocks = line_blocks_from_str("a: 1");
+        assert_eq!(
+            try_sections(&blocks, (fallible(reg),)),
+            Ok(("1".to_string(),))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+
+use super::{IPoint3, OFFSETS6, OFFSETS26, pt3};
+
+/// A dense 3D grid of voxels, with the origin at (0, 0, 0).
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DenseField3<T> {
+    width: isize,
+    height: isize,
+    depth: isize,
+    data: Vec<T>,
+}
+
+impl<T: Clone> DenseField3<T> {
+    pub fn new(width: isize, height: isize, depth: isize, val: T) -> Self {
+        assert!(width > 0 && height > 0 && depth > 0);
+
+        DenseField3 {
+            width,
+            height,
+            depth,
+            data: vec![val; (width * height * depth).try_into().unwrap()],
+        }
+    }
+
+    /// Create a field just big enough to hold every point, setting those
+    /// points to `val` and everything else to `empty`. Points must not be
+    /// negative.
+    pub fn from_points(points: impl IntoIterator<Item = IPoint3>, val: T, empty: T) -> Self {
+        let points: Vec<_> = points.into_iter().collect();
+        let max = points.iter().fold(pt3(0, 0, 0), |m, p| {
+            pt3(m.x.max(p.x), m.y.max(p.y), m.z.max(p.z))
+        });
+
+        let mut field = DenseField3::new(max.x + 1, max.y + 1, max.z + 1, empty);
+        for p in points {
+            *field.get_mut(p) = val.clone();
+        }
+        field
+    }
+}
+
+impl<T> DenseField3<T> {
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    pub fn depth(&self) -> isize {
+        self.depth
+    }
+
+    pub fn data(&self) -> &Vec<T> {
+        &self.data
+    }
+
+    pub fn in_bounds(&self, p: IPoint3) -> bool {
+        (0..self.width).contains(&p.x)
+            && (0..self.height).contains(&p.y)
+            && (0..self.depth).contains(&p.z)
+    }
+
+    fn index(&self, p: IPoint3) -> usize {
+        ((p.z * self.height + p.y) * self.width + p.x) as usize
+    }
+
+    pub fn get(&self, p: IPoint3) -> &T {
+        assert!(self.in_bounds(p), "{p:?} out of bounds");
+        &self.data[self.index(p)]
+    }
+
+    pub fn get_mut(&mut self, p: IPoint3) -> &mut T {
+        assert!(self.in_bounds(p), "{p:?} out of bounds");
+        let i = self.index(p);
+        &mut self.data[i]
+    }
+
+    pub fn try_get(&self, p: IPoint3) -> Option<&T> {
+        self.in_bounds(p).then(|| &self.data[self.index(p)])
+    }
+
+    pub fn try_get_mut(&mut self, p: IPoint3) -> Option<&mut T> {
+        if self.in_bounds(p) {
+            let i = self.index(p);
+            Some(&mut self.data[i])
+        } else {
+            None
+        }
+    }
+
+    /// Iterate through every point, x changing fastest then y then z.
+    pub fn points(&self) -> impl Iterator<Item = IPoint3> {
+        let (w, h, d) = (self.width, self.height, self.depth);
+        (0..d).flat_map(move |z| (0..h).flat_map(move |y| (0..w).map(move |x| pt3(x, y, z))))
+    }
+
+    /// Face neighbours of this point, skipping any outside of the field.
+    pub fn neighbours6_bounded(&self, p: IPoint3) -> impl Iterator<Item = (&T, IPoint3)> {
+        OFFSETS6
+            .into_iter()
+            .filter_map(move |d| self.try_get(p + d).map(|v| (v, p + d)))
+    }
+
+    /// Face, edge and corner neighbours of this point, skipping any outside
+    /// of the field.
+    pub fn neighbours26_bounded(&self, p: IPoint3) -> impl Iterator<Item = (&T, IPoint3)> {
+        OFFSETS26
+            .into_iter()
+            .filter_map(move |d| self.try_get(p + d).map(|v| (v, p + d)))
+    }
+
+    /// Count the faces of solid cells that aren't touching another solid
+    /// cell. Faces on the edge of the field count as exposed.
+    pub fn surface_area(&self, is_solid: impl Fn(&T) -> bool) -> usize {
+        self.points()
+            .filter(|p| is_solid(self.get(*p)))
+            .map(|p| {
+                OFFSETS6
+                    .iter()
+                    .filter(|d| !self.try_get(p + **d).is_some_and(&is_solid))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Like `surface_area`, but only counting faces that can be reached from
+    /// outside of the field. Pockets of air enclosed by solid cells don't
+    /// count.
+    pub fn exterior_surface_area(&self, is_solid: impl Fn(&T) -> bool) -> usize {
+        // Flood fill the air around the field, including a one cell border
+        // outside of it. Every time the fill bumps into a solid cell that's
+        // an exposed face.
+        let inside = |p: IPoint3| {
+            (-1..=self.width).contains(&p.x)
+                && (-1..=self.height).contains(&p.y)
+                && (-1..=self.depth).contains(&p.z)
+        };
+        let solid = |p: IPoint3| self.try_get(p).is_some_and(&is_solid);
+
+        let start = pt3(-1, -1, -1);
+        let mut seen = DenseField3::new(self.width + 2, self.height + 2, self.depth + 2, false);
+        let offset = pt3(1, 1, 1);
+        *seen.get_mut(start + offset) = true;
+
+        let mut q = VecDeque::from([start]);
+        let mut faces = 0;
+
+        while let Some(p) = q.pop_front() {
+            for n in p.neighbours6().filter(|n| inside(*n)) {
+                if solid(n) {
+                    faces += 1;
+                } else if !*seen.get(n + offset) {
+                    *seen.get_mut(n + offset) = true;
+                    q.push_back(n);
+                }
+            }
+        }
+
+        faces
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::three::{DenseField3, pt3};
+
+    #[test]
+    fn get_and_neighbours() {
+        let mut field = DenseField3::new(3, 4, 5, 0);
+        *field.get_mut(pt3(2, 3, 4)) = 7;
+        assert_eq!(*field.get(pt3(2, 3, 4)), 7);
+        assert_eq!(field.try_get(pt3(3, 0, 0)), None);
+        assert_eq!(field.points().count(), 60);
+        assert_eq!(field.points().last(), Some(pt3(2, 3, 4)));
+
+        assert_eq!(field.neighbours6_bounded(pt3(0, 0, 0)).count(), 3);
+        assert_eq!(field.neighbours6_bounded(pt3(1, 1, 1)).count(), 6);
+        assert_eq!(field.neighbours26_bounded(pt3(0, 0, 0)).count(), 7);
+        assert_eq!(field.neighbours26_bounded(pt3(1, 1, 1)).count(), 26);
+    }
+
+    #[test]
+    fn surface_area() {
+        let field = DenseField3::from_points([pt3(1, 1, 1), pt3(2, 1, 1)], true, false);
+        assert_eq!(field.surface_area(|c| *c), 10);
+        assert_eq!(field.exterior_surface_area(|c| *c), 10);
+
+        // Example from 2022 day 18, which has a single trapped air pocket.
+        let example =
+            "2,2,2 1,2,2 3,2,2 2,1,2 2,3,2 2,2,1 2,2,3 2,2,4 2,2,6 1,2,5 3,2,5 2,1,5 2,3,5";
+        let points = example.split(' ').map(|p| {
+            let [x, y, z] = p.split(',').map(|n| n.parse().unwrap()).collect::<Vec<_>>()[..] else {
+                panic!()
+            };
+            pt3(x, y, z)
+        });
+        let field = DenseField3::from_points(points, true, false);
+        assert_eq!(field.surface_area(|c| *c), 64);
+        assert_eq!(field.exterior_surface_area(|c| *c), 58);
+    }
+}
@@ -0,0 +1,5 @@
+mod point;
+pub use point::*;
+
+mod field;
+pub use field::*;
@@ -0,0 +1,194 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use crate::two::Num;
+
+pub type IPoint3 = Point3<isize>;
+
+/// The six face neighbour offsets, along each axis.
+pub const OFFSETS6: [IPoint3; 6] = [
+    pt3(1, 0, 0),
+    pt3(-1, 0, 0),
+    pt3(0, 1, 0),
+    pt3(0, -1, 0),
+    pt3(0, 0, 1),
+    pt3(0, 0, -1),
+];
+
+/// All 26 offsets to the cells surrounding a point, including diagonals.
+pub const OFFSETS26: [IPoint3; 26] = offsets26();
+
+const fn offsets26() -> [IPoint3; 26] {
+    let mut offsets = [pt3(0, 0, 0); 26];
+    let mut i = 0;
+    let mut n = 0;
+    // Count through -1..=1 for each axis, skipping the centre.
+    while n < 27 {
+        if n != 13 {
+            offsets[i] = pt3(n % 3 - 1, (n / 3) % 3 - 1, n / 9 - 1);
+            i += 1;
+        }
+        n += 1;
+    }
+    offsets
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[inline]
+pub const fn pt3<T: Num>(x: T, y: T, z: T) -> Point3<T> {
+    Point3::new(x, y, z)
+}
+
+impl<T: Num> Point3<T> {
+    #[inline]
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn norm_squared(&self) -> T {
+        self.x * self.x + self.y * self.y + self.z * self.z
+    }
+
+    /// Square of the straight line distance between two points.
+    pub fn dist_squared(&self, p: Self) -> T {
+        (*self - p).norm_squared()
+    }
+
+    pub fn dot(&self, p: Self) -> T {
+        self.x * p.x + self.y * p.y + self.z * p.z
+    }
+}
+
+impl Point3<isize> {
+    pub fn norm(&self) -> f64 {
+        (self.norm_squared() as f64).sqrt()
+    }
+
+    // Absolute distance between two points, only along the axes.
+    pub fn taxicab_dist(&self, p: Self) -> usize {
+        self.x.abs_diff(p.x) + self.y.abs_diff(p.y) + self.z.abs_diff(p.z)
+    }
+
+    /// Points sharing a face with this one.
+    pub fn neighbours6(self) -> impl Iterator<Item = IPoint3> {
+        OFFSETS6.into_iter().map(move |d| self + d)
+    }
+
+    /// Points sharing a face, edge or corner with this one.
+    pub fn neighbours26(self) -> impl Iterator<Item = IPoint3> {
+        OFFSETS26.into_iter().map(move |d| self + d)
+    }
+}
+
+impl Point3<f64> {
+    pub fn norm(&self) -> f64 {
+        self.norm_squared().sqrt()
+    }
+}
+
+impl<T: Num> Add<Point3<T>> for Point3<T> {
+    type Output = Point3<T>;
+
+    fn add(self, rhs: Point3<T>) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl<T: Num> AddAssign<Point3<T>> for Point3<T> {
+    fn add_assign(&mut self, rhs: Point3<T>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Num> Sub<Point3<T>> for Point3<T> {
+    type Output = Point3<T>;
+
+    fn sub(self, rhs: Point3<T>) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: Num> Mul<T> for Point3<T> {
+    type Output = Point3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+impl Mul<Point3<isize>> for isize {
+    type Output = Point3<isize>;
+
+    fn mul(self, rhs: Point3<isize>) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Neg for Point3<isize> {
+    type Output = Point3<isize>;
+
+    fn neg(self) -> Self::Output {
+        -1 * self
+    }
+}
+
+impl Mul<Point3<f64>> for f64 {
+    type Output = Point3<f64>;
+
+    fn mul(self, rhs: Point3<f64>) -> Self::Output {
+        rhs * self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn ops() {
+        let p1 = pt3(1, 2, 3);
+        let p2 = pt3(4, 5, 6);
+        assert_eq!(p1 + p2, pt3(5, 7, 9));
+        assert_eq!(p1 - p2, pt3(-3, -3, -3));
+        assert_eq!(p1 * 2, pt3(2, 4, 6));
+        assert_eq!(2 * p1, pt3(2, 4, 6));
+        assert_eq!(-p1, pt3(-1, -2, -3));
+        assert_eq!(0.5 * pt3(1.0, 2.0, 4.0), pt3(0.5, 1.0, 2.0));
+
+        assert_eq!(pt3(2, 3, 6).norm(), 7.0);
+        assert_eq!(p1.dist_squared(p2), 27);
+        assert_eq!(p1.dot(p2), 32);
+        assert_eq!(p1.taxicab_dist(pt3(0, 4, -1)), 7);
+    }
+
+    #[test]
+    fn offsets() {
+        let all: HashSet<_> = OFFSETS26.into_iter().collect();
+        assert_eq!(all.len(), 26);
+        assert!(!all.contains(&pt3(0, 0, 0)));
+        assert!(OFFSETS6.iter().all(|d| all.contains(d)));
+        assert!(
+            all.iter()
+                .all(|d| d.x.abs() <= 1 && d.y.abs() <= 1 && d.z.abs() <= 1)
+        );
+    }
+}
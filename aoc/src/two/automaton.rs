@@ -0,0 +1,351 @@
+use std::mem;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::{DenseField, IPoint, SparseField, Stencil, Topology, pt};
+
+/// Steps a field from one generation to the next with a rule that works out
+/// each cell's next value from its neighbours, like the game of life. Keeps a
+/// second field around to write the next generation into, so stepping
+/// doesn't allocate.
+///
+/// Works with `DenseField`, where the rule gives every cell's next value, and
+/// `SparseField`, where the rule says which cells are set next, looking at
+/// every set cell and the empty cells around them.
+#[derive(Debug, Clone)]
+pub struct Automaton<F> {
+    current: F,
+    next: F,
+    generation: usize,
+}
+
+/// A cell of a field and what's around it, passed to an automaton's rule.
+#[derive(Debug)]
+pub struct Neighbourhood<'a, F> {
+    pub field: &'a F,
+    pub p: IPoint,
+}
+
+// Manual impls so that the field itself doesn't need to be Copy.
+impl<F> Clone for Neighbourhood<'_, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for Neighbourhood<'_, F> {}
+
+/// Fields an `Automaton` can step.
+pub trait AutomatonField {
+    /// Somewhere to write the next generation into.
+    fn scratch(&self) -> Self;
+}
+
+impl<T: Clone> AutomatonField for DenseField<T> {
+    fn scratch(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl<T> AutomatonField for SparseField<T> {
+    fn scratch(&self) -> Self {
+        SparseField::new()
+    }
+}
+
+impl<F: AutomatonField> Automaton<F> {
+    pub fn new(field: F) -> Self {
+        Automaton {
+            next: field.scratch(),
+            current: field,
+            generation: 0,
+        }
+    }
+}
+
+impl<F> Automaton<F> {
+    /// The current generation.
+    pub fn field(&self) -> &F {
+        &self.current
+    }
+
+    pub fn into_field(self) -> F {
+        self.current
+    }
+
+    /// How many times we've stepped.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn swap(&mut self) {
+        mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+    }
+}
+
+impl<T: Clone + PartialEq> Automaton<DenseField<T>> {
+    /// Move on a generation, returning whether any cell changed.
+    pub fn step(&mut self, rule: impl Fn(Neighbourhood<DenseField<T>>) -> T) -> bool {
+        let changed = self.current.step_into(&mut self.next, rule);
+        self.swap();
+        changed
+    }
+
+    /// Same as `step` but works out cells in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_step(&mut self, rule: impl Fn(Neighbourhood<DenseField<T>>) -> T + Sync) -> bool
+    where
+        T: Send + Sync,
+    {
+        let current = &self.current;
+        let changed = self
+            .next
+            .data_mut()
+            .par_iter_mut()
+            .enumerate()
+            .map(|(i, cell)| {
+                let p = pt(i as isize % current.width(), i as isize / current.width());
+                *cell = rule(Neighbourhood { field: current, p });
+                cell != current.get(p)
+            })
+            .reduce(|| false, |a, b| a || b);
+        self.swap();
+        changed
+    }
+
+    /// Keep stepping until nothing changes, returning the generation that
+    /// happened on.
+    pub fn run_until_stable(&mut self, rule: impl Fn(Neighbourhood<DenseField<T>>) -> T) -> usize {
+        while self.step(&rule) {}
+        self.generation
+    }
+}
+
+impl<T: Clone + PartialEq> Automaton<SparseField<T>> {
+    // Every set cell and the empty cells around them, which are the only
+    // cells that could be set next.
+    fn candidates(&self) -> Vec<IPoint> {
+        let mut candidates: Vec<IPoint> = self
+            .current
+            .points()
+            .flat_map(|p| Stencil::EIGHT.offsets().iter().map(move |d| p + *d))
+            .chain(self.current.points())
+            .collect();
+        candidates.sort_unstable_by_key(|p| (p.y, p.x));
+        candidates.dedup();
+        candidates
+    }
+
+    /// Move on a generation, returning whether anything changed. The rule
+    /// returns what a cell should be next, or None to leave it empty.
+    ///
+    /// There's no parallel version of this, since sparse fields cache their
+    /// bounds in a way that can't be shared between threads.
+    pub fn step(&mut self, rule: impl Fn(Neighbourhood<SparseField<T>>) -> Option<T>) -> bool {
+        let candidates = self.candidates();
+        self.next.clear();
+        for p in candidates {
+            if let Some(v) = rule(Neighbourhood {
+                field: &self.current,
+                p,
+            }) {
+                self.next.insert(p, v);
+            }
+        }
+        let changed = self.next != self.current;
+        self.swap();
+        changed
+    }
+
+    /// Keep stepping until nothing changes, returning the generation that
+    /// happened on.
+    pub fn run_until_stable(
+        &mut self,
+        rule: impl Fn(Neighbourhood<SparseField<T>>) -> Option<T>,
+    ) -> usize {
+        while self.step(&rule) {}
+        self.generation
+    }
+}
+
+impl<T: Clone + PartialEq> DenseField<T> {
+    /// Move on a generation in place, see `Automaton::step`. This is just for
+    /// a one off step, as it allocates a whole new field every time. When
+    /// stepping in a loop use an `Automaton`, which reuses its second field.
+    pub fn step(&mut self, rule: impl Fn(Neighbourhood<DenseField<T>>) -> T) -> bool {
+        let mut next = self.clone();
+        let changed = self.step_into(&mut next, rule);
+        *self = next;
+        changed
+    }
+
+    // Write the next generation into a field of the same size.
+    fn step_into(
+        &self,
+        next: &mut DenseField<T>,
+        rule: impl Fn(Neighbourhood<DenseField<T>>) -> T,
+    ) -> bool {
+        let mut changed = false;
+        for (i, cell) in next.data_mut().iter_mut().enumerate() {
+            let p = pt(i as isize % self.width(), i as isize / self.width());
+            *cell = rule(Neighbourhood { field: self, p });
+            changed |= cell != self.get(p);
+        }
+        changed
+    }
+}
+
+impl<'a, T> Neighbourhood<'a, DenseField<T>> {
+    pub fn value(&self) -> &'a T {
+        self.field.get(self.p)
+    }
+
+    /// The eight cells around this one, stopping at the edges.
+    pub fn neighbours(&self) -> impl Iterator<Item = (&'a T, IPoint)> {
+        self.field.neighbours8_bounded(self.p)
+    }
+
+    /// Neighbours with any stencil and topology, see `DenseField::neighbours`.
+    pub fn neighbours_with(
+        &self,
+        stencil: Stencil<'a>,
+        topology: impl Topology + 'a,
+    ) -> impl Iterator<Item = (&'a T, IPoint)> {
+        self.field.neighbours(self.p, stencil, topology)
+    }
+
+    /// How many of the eight cells around this one match.
+    pub fn count(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.neighbours().filter(|(v, _)| pred(v)).count()
+    }
+}
+
+impl<'a, T> Neighbourhood<'a, SparseField<T>> {
+    pub fn value(&self) -> Option<&'a T> {
+        self.field.get(self.p)
+    }
+
+    /// The set cells out of the eight around this one.
+    pub fn neighbours(&self) -> impl Iterator<Item = (&'a T, IPoint)> {
+        self.field.neighbours8_bounded(self.p)
+    }
+
+    /// How many of the set cells around this one match.
+    pub fn count(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.neighbours().filter(|(v, _)| pred(v)).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{Automaton, DenseField, IPoint, Neighbourhood, SparseField, pt};
+
+    fn life(n: Neighbourhood<DenseField<bool>>) -> bool {
+        matches!((n.value(), n.count(|alive| *alive)), (true, 2) | (_, 3))
+    }
+
+    #[test]
+    fn dense() {
+        let mut field = DenseField::new(5, 5, false);
+        for x in 1..4 {
+            *field.get_mut(pt(x, 2)) = true;
+        }
+        let start = field.clone();
+
+        // A blinker flips between horizontal and vertical.
+        let mut life_field = Automaton::new(field);
+        assert!(life_field.step(life));
+        assert!(*life_field.field().get(pt(2, 1)));
+        assert!(!*life_field.field().get(pt(1, 2)));
+        assert!(life_field.step(life));
+        assert_eq!(*life_field.field(), start);
+        assert_eq!(life_field.generation(), 2);
+
+        // Stepping the field directly does the same.
+        let mut stepped = start.clone();
+        assert!(stepped.step(life));
+        assert!(stepped.step(life));
+        assert_eq!(stepped, start);
+
+        // Rolls of paper from 2025 day 4 get removed until the rest are all
+        // crowded enough. Removing them in place ends up the same.
+        let field = DenseField::<u8>::from_lines(
+            ["..@@.", "@@@@@", ".@@@.", "@...@"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        );
+        let crowded =
+            |f: &DenseField<u8>, p| f.neighbours8_bounded(p).filter(|n| *n.0 == b'@').count() >= 4;
+        let mut naive = field.clone();
+        let mut removed = true;
+        while removed {
+            removed = false;
+            for p in naive.points() {
+                if *naive.get(p) == b'@' && !crowded(&naive, p) {
+                    *naive.get_mut(p) = b'.';
+                    removed = true;
+                }
+            }
+        }
+
+        let mut rolls = Automaton::new(field);
+        let gens = rolls.run_until_stable(|n| match n.value() {
+            b'@' if n.count(|c| *c == b'@') < 4 => b'.',
+            c => *c,
+        });
+        assert_eq!(*rolls.field(), naive);
+        assert_eq!(gens, rolls.generation());
+        assert!(gens > 2);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let mut field = DenseField::new(20, 20, false);
+        for p in [pt(1, 0), pt(2, 1), pt(0, 2), pt(1, 2), pt(2, 2)] {
+            *field.get_mut(p) = true;
+        }
+        let mut serial = Automaton::new(field.clone());
+        let mut parallel = Automaton::new(field);
+        for _ in 0..10 {
+            assert_eq!(serial.step(life), parallel.par_step(life));
+            assert_eq!(serial.field(), parallel.field());
+        }
+    }
+
+    #[test]
+    fn sparse() {
+        let glider: SparseField<()> = [pt(1, 0), pt(2, 1), pt(0, 2), pt(1, 2), pt(2, 2)]
+            .into_iter()
+            .map(|p| (p, ()))
+            .collect();
+        let mut life = Automaton::new(glider.clone());
+        for _ in 0..4 {
+            assert!(life.step(|n| match (n.value(), n.neighbours().count()) {
+                (Some(_), 2) | (_, 3) => Some(()),
+                _ => None,
+            }));
+        }
+
+        // After four generations a glider has moved one down and right.
+        let moved: SparseField<()> = glider.iter().map(|(p, _)| (p + pt(1, 1), ())).collect();
+        assert_eq!(*life.field(), moved);
+        assert_eq!(life.field().bounds(), Some((pt(1, 1), pt(3, 3))));
+
+        // Nothing changes for a block.
+        let block: SparseField<()> = [pt(0, 0), pt(1, 0), pt(0, 1), pt(1, 1)]
+            .into_iter()
+            .map(|p: IPoint| (p, ()))
+            .collect();
+        let mut life = Automaton::new(block);
+        assert_eq!(
+            life.run_until_stable(
+                |n| (n.value().is_some() && n.neighbours().count() == 3).then_some(())
+            ),
+            1
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::three::{IPoint3, pt3};
+
+use super::{DenseField, Dirn, IPoint, Topology, pt};
+
+/// A flat map made of six square faces that fold up into a cube, so walking
+/// off the edge of one face carries on across the face it's glued to. Works
+/// out the gluing itself, so any of the eleven cube nets work.
+#[derive(Debug, Clone)]
+pub struct CubeNet {
+    size: isize,
+    faces: Vec<Face>,
+    // Face index for each face sized block of the map that's part of the net.
+    blocks: HashMap<IPoint, usize>,
+}
+
+/// Where a face ended up once folded, as unit vectors for its outward normal
+/// and the directions of the map's x and y axes across it.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    block: IPoint,
+    normal: IPoint3,
+    right: IPoint3,
+    down: IPoint3,
+}
+
+impl Face {
+    fn dirn(&self, d: Dirn) -> IPoint3 {
+        match d {
+            Dirn::Up => -self.down,
+            Dirn::Left => -self.right,
+            Dirn::Down => self.down,
+            Dirn::Right => self.right,
+        }
+    }
+
+    /// Roll over the edge in direction `d`. The face we land on points the
+    /// way we were going, and carrying on means heading into the cube.
+    fn roll(&self, d: Dirn) -> Face {
+        let mut face = Face {
+            normal: self.dirn(d),
+            ..*self
+        };
+        match d {
+            Dirn::Up => face.down = self.normal,
+            Dirn::Left => face.right = self.normal,
+            Dirn::Down => face.down = -self.normal,
+            Dirn::Right => face.right = -self.normal,
+        }
+        face
+    }
+}
+
+const DIRNS: [Dirn; 4] = [Dirn::Up, Dirn::Left, Dirn::Down, Dirn::Right];
+
+impl CubeNet {
+    /// Find the net in a map where `on_net` says which points are part of a
+    /// face. The face size is worked out from how many points there are.
+    /// Panics if the points don't fold into a cube.
+    pub fn new(width: isize, height: isize, on_net: impl Fn(IPoint) -> bool) -> Self {
+        let count = (0..height)
+            .flat_map(|y| (0..width).map(move |x| pt(x, y)))
+            .filter(|p| on_net(*p))
+            .count();
+        let size = (count / 6).isqrt() as isize;
+        assert!(
+            size > 0 && (size * size * 6) as usize == count,
+            "{count} points can't make six square faces"
+        );
+
+        let mut blocks: Vec<IPoint> = vec![];
+        for by in 0..height / size {
+            for bx in 0..width / size {
+                if on_net(pt(bx * size, by * size)) {
+                    blocks.push(pt(bx, by));
+                }
+            }
+        }
+        assert_eq!(blocks.len(), 6, "faces aren't aligned to a {size} grid");
+
+        // Fold up the net by walking across it from the first face, rolling
+        // the orientation over each edge we cross.
+        let first = Face {
+            block: blocks[0],
+            normal: pt3(0, 0, -1),
+            right: pt3(1, 0, 0),
+            down: pt3(0, 1, 0),
+        };
+        let mut folded = HashMap::from([(first.block, first)]);
+        let mut q = VecDeque::from([first]);
+        while let Some(face) = q.pop_front() {
+            for d in DIRNS {
+                let block = face.block + d.as_point();
+                if blocks.contains(&block) && !folded.contains_key(&block) {
+                    let next = Face {
+                        block,
+                        ..face.roll(d)
+                    };
+                    folded.insert(block, next);
+                    q.push_back(next);
+                }
+            }
+        }
+
+        let faces: Vec<Face> = blocks.iter().map(|b| folded[b]).collect();
+        assert_eq!(folded.len(), 6, "faces of the net aren't connected");
+        for (i, a) in faces.iter().enumerate() {
+            for b in &faces[i + 1..] {
+                assert!(
+                    a.normal != b.normal,
+                    "faces at {:?} and {:?} fold on top of each other",
+                    a.block,
+                    b.block
+                );
+            }
+        }
+
+        CubeNet {
+            size,
+            blocks: blocks.iter().enumerate().map(|(i, b)| (*b, i)).collect(),
+            faces,
+        }
+    }
+
+    /// Find the net in a field, see `new`.
+    pub fn from_field<T>(field: &DenseField<T>, on_net: impl Fn(&T) -> bool) -> Self {
+        Self::new(field.width(), field.height(), |p| {
+            field.try_get(p).is_some_and(&on_net)
+        })
+    }
+
+    /// Width and height of each face.
+    pub fn face_size(&self) -> isize {
+        self.size
+    }
+
+    /// Which face a point is on, numbered from 0 to 5 in row major order of
+    /// the net. None if the point isn't part of the net.
+    pub fn face(&self, p: IPoint) -> Option<usize> {
+        let block = pt(p.x.div_euclid(self.size), p.y.div_euclid(self.size));
+        self.blocks.get(&block).copied()
+    }
+
+    /// Take one step from a point on the net. Stepping off the edge of a face
+    /// wraps onto the face glued to it, which can turn us around. Returns the
+    /// new point and facing.
+    pub fn step(&self, p: IPoint, facing: Dirn) -> (IPoint, Dirn) {
+        let next = p + facing.as_point();
+        if self.face(next).is_some() {
+            return (next, facing);
+        }
+
+        let from = self.faces[self.face(p).expect("point isn't on the net")];
+        let heading = from.dirn(facing);
+        let to = self.faces.iter().find(|f| f.normal == heading).unwrap();
+
+        // Use doubled 3D coordinates with the cube centred on the origin, so
+        // cells have integer centres. Stepping over the edge goes half a cell
+        // out, then half a cell down the next face.
+        let size = self.size;
+        let local = p - from.block * size;
+        let centre = size * from.normal
+            + (2 * local.x + 1 - size) * from.right
+            + (2 * local.y + 1 - size) * from.down;
+        let landed = centre + heading - from.normal;
+
+        let local = pt(
+            (landed.dot(to.right) + size - 1) / 2,
+            (landed.dot(to.down) + size - 1) / 2,
+        );
+        let facing = DIRNS
+            .into_iter()
+            .find(|d| to.dirn(*d) == -from.normal)
+            .unwrap();
+        (to.block * size + local, facing)
+    }
+}
+
+/// Single steps that leave the net wrap around the cube. Anything else
+/// landing off the net, like a diagonal step, goes nowhere.
+impl Topology for CubeNet {
+    fn resolve(&self, _: isize, _: isize, from: IPoint, to: IPoint) -> Option<IPoint> {
+        if self.face(to).is_some() {
+            return Some(to);
+        }
+        DIRNS
+            .into_iter()
+            .find(|d| from + d.as_point() == to)
+            .map(|d| self.step(from, d).0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{CubeNet, DenseField, Dirn, IPoint, Stencil, pt};
+
+    // Scale up a net drawn with one character per face.
+    fn net(blocks: &[&str], size: isize) -> CubeNet {
+        CubeNet::new(20, 20, |p: IPoint| {
+            let row = blocks.get((p.y / size) as usize).map(|r| r.as_bytes());
+            row.and_then(|r| r.get((p.x / size) as usize)) == Some(&b'#')
+        })
+    }
+
+    fn all_nets() -> Vec<Vec<String>> {
+        let mut nets = vec![];
+        for (top, bottom) in [(0, 0), (0, 1), (0, 2), (0, 3), (1, 1), (1, 2)] {
+            let row = |x| format!("{}#", " ".repeat(x));
+            nets.push(vec![row(top), "####".to_string(), row(bottom)]);
+        }
+        for bottom in 1..4 {
+            let row = format!("{}#", " ".repeat(bottom));
+            nets.push(vec!["##".to_string(), " ###".to_string(), row]);
+        }
+        nets.push(vec![
+            "##".to_string(),
+            " ##".to_string(),
+            "  ##".to_string(),
+        ]);
+        nets.push(vec!["###".to_string(), "  ###".to_string()]);
+        nets
+    }
+
+    #[test]
+    fn every_net_folds() {
+        let nets = all_nets();
+        assert_eq!(nets.len(), 11);
+
+        for blocks in nets {
+            let blocks: Vec<&str> = blocks.iter().map(|s| s.as_str()).collect();
+            let cube = net(&blocks, 3);
+            assert_eq!(cube.face_size(), 3);
+
+            let on_net: Vec<IPoint> = (0..20)
+                .flat_map(|y| (0..20).map(move |x| pt(x, y)))
+                .filter(|p| cube.face(*p).is_some())
+                .collect();
+            for &p in &on_net {
+                for facing in [Dirn::Up, Dirn::Left, Dirn::Down, Dirn::Right] {
+                    // Turning around and stepping back undoes a step.
+                    let (q, g) = cube.step(p, facing);
+                    let back = g.clockwise().clockwise();
+                    let (r, h) = cube.step(q, back);
+                    assert_eq!((r, h.clockwise().clockwise()), (p, facing), "{blocks:?}");
+
+                    // Walking all the way around comes back again.
+                    let (mut q, mut g) = (p, facing);
+                    for _ in 0..12 {
+                        (q, g) = cube.step(q, g);
+                    }
+                    assert_eq!((q, g), (p, facing), "{blocks:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_net() {
+        net(&["##", "##", "##"], 2);
+    }
+
+    #[test]
+    fn walk() {
+        // Example from 2022 day 22.
+        let map = [
+            "        ...#",
+            "        .#..",
+            "        #...",
+            "        ....",
+            "...#.......#",
+            "........#...",
+            "..#....#....",
+            "..........#.",
+            "        ...#....",
+            "        .....#..",
+            "        .#......",
+            "        ......#.",
+        ];
+        let lines = map.iter().map(|l| format!("{l:16}")).collect();
+        let field = DenseField::<u8>::from_lines(lines);
+        let cube = CubeNet::from_field(&field, |c| *c != b' ');
+        assert_eq!(cube.face_size(), 4);
+
+        // Off the right of face 3 and onto the top of face 5, facing down.
+        assert_eq!(cube.step(pt(11, 5), Dirn::Right), (pt(14, 8), Dirn::Down));
+        // Off the bottom of face 4 and up the bottom of face 1.
+        assert_eq!(cube.step(pt(10, 11), Dirn::Down), (pt(1, 7), Dirn::Up));
+        // Off the top of face 2 and onto the left of face 0.
+        assert_eq!(cube.step(pt(6, 4), Dirn::Up), (pt(8, 2), Dirn::Right));
+
+        let (mut p, mut facing) = (pt(8, 0), Dirn::Right);
+        for instr in [
+            "10", "R", "5", "L", "5", "R", "10", "L", "4", "R", "5", "L", "5",
+        ] {
+            match instr {
+                "R" => facing = facing.clockwise(),
+                "L" => facing = facing.anticlockwise(),
+                n => {
+                    for _ in 0..n.parse().unwrap() {
+                        let (q, g) = cube.step(p, facing);
+                        if *field.get(q) == b'#' {
+                            break;
+                        }
+                        (p, facing) = (q, g);
+                    }
+                }
+            }
+        }
+        assert_eq!((p, facing), (pt(6, 4), Dirn::Up));
+
+        // Neighbours wrap around the cube too.
+        let n: Vec<_> = field
+            .neighbours(pt(8, 0), Stencil::FOUR, &cube)
+            .map(|n| n.1)
+            .collect();
+        assert_eq!(n, vec![pt(3, 4), pt(4, 4), pt(9, 0), pt(8, 1)]);
+    }
+}
@@ -1,11 +1,40 @@
 use std::{
+    collections::HashMap,
+    error::Error,
     fmt::{Debug, Display},
-    mem,
+    iter::StepBy,
+    slice,
 };
 
 use crate::two::Point;
 
-use super::{pt, IPoint};
+use super::{IPoint, pt};
+
+mod orient;
+pub use orient::*;
+
+mod window;
+pub use window::*;
+
+mod topology;
+pub use topology::*;
+
+mod region;
+pub use region::*;
+
+mod ray;
+
+mod pattern;
+pub use pattern::*;
+
+mod tilt;
+pub use tilt::*;
+
+mod distance;
+pub use distance::*;
+
+mod render;
+pub use render::*;
 
 /// A dense 2D field of cells. Has methods to get and mutate cells as if it was
 /// bounded, or an infinite toriodal surface. Allows getting neighbors for
@@ -28,14 +57,6 @@ impl<T: Clone> DenseField<T> {
         }
     }
 
-    pub fn width(&self) -> isize {
-        self.width
-    }
-
-    pub fn height(&self) -> isize {
-        self.height
-    }
-
     /// Brute force find a given value in the field.
     pub fn find(&self, val: &T) -> Option<Point<isize>>
     where
@@ -55,19 +76,6 @@ impl<T: Clone> DenseField<T> {
     pub fn data(&self) -> &Vec<T> {
         &self.data
     }
-
-    /// Rotate the field by 90 degrees clockwise. This obviously flips the
-    /// height and width if they're different. Not an efficient operation.
-    pub fn rotate_clockwise(&mut self) {
-        let old = self.clone();
-        mem::swap(&mut self.width, &mut self.height);
-
-        for oldy in 0..old.height {
-            for oldx in 0..old.width {
-                *self.get_mut(pt(old.height - oldy - 1, oldx)) = old.get(pt(oldx, oldy)).clone();
-            }
-        }
-    }
 }
 
 impl<T: Display> DenseField<T> {
@@ -82,6 +90,19 @@ impl<T: Display> DenseField<T> {
 }
 
 impl<T> DenseField<T> {
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// All cells in row major order, to write to directly.
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
     pub fn get(&self, p: IPoint) -> &T {
         assert!(p.x >= 0 && p.x < self.width);
         assert!(p.y >= 0 && p.y < self.height);
@@ -110,6 +131,36 @@ impl<T> DenseField<T> {
         }
     }
 
+    /// Borrow a single row of the field.
+    pub fn row(&self, y: isize) -> &[T] {
+        assert!(y >= 0 && y < self.height);
+        let w = self.width as usize;
+        &self.data[y as usize * w..(y as usize + 1) * w]
+    }
+
+    pub fn row_mut(&mut self, y: isize) -> &mut [T] {
+        assert!(y >= 0 && y < self.height);
+        let w = self.width as usize;
+        &mut self.data[y as usize * w..(y as usize + 1) * w]
+    }
+
+    /// Iterate over the rows from top to bottom.
+    pub fn rows(&self) -> slice::ChunksExact<'_, T> {
+        self.data.chunks_exact(self.width as usize)
+    }
+
+    /// Iterate over the cells of a single column from top to bottom.
+    pub fn column(&self, x: isize) -> StepBy<slice::Iter<'_, T>> {
+        assert!(x >= 0 && x < self.width);
+        self.data[x as usize..].iter().step_by(self.width as usize)
+    }
+
+    /// Iterate over the columns from left to right. Each column is itself an
+    /// iterator over its cells, see `column`.
+    pub fn columns(&self) -> impl Iterator<Item = StepBy<slice::Iter<'_, T>>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
     /// `get` but x and y are wrapped around like a torus.
     pub fn wrapping_get(&self, p: IPoint) -> (&T, Point<isize>) {
         let x = p.x % self.width;
@@ -135,82 +186,32 @@ impl<T> DenseField<T> {
     /// Points outside of the field are not returned. Each value contains the
     /// neighbout value and the point of that neighbour.
     pub fn neighbours8_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| (self.try_get(pt(x, y)), Point::new(x, y));
-        [
-            p(x - 1, y - 1),
-            p(x, y - 1),
-            p(x + 1, y - 1),
-            p(x - 1, y),
-            p(x + 1, y),
-            p(x - 1, y + 1),
-            p(x, y + 1),
-            p(x + 1, y + 1),
-        ]
-        .into_iter()
-        .filter_map(|(nei, p)| nei.map(|nei| (nei, p)))
+        self.neighbours(p, Stencil::EIGHT, Bounded)
     }
 
     /// Return neighbours as if the field is the surface of a torus.
     pub fn neighbours8_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| self.wrapping_get(pt(x, y));
-
-        [
-            p(x - 1, y - 1),
-            p(x, y - 1),
-            p(x + 1, y - 1),
-            p(x - 1, y),
-            p(x + 1, y),
-            p(x - 1, y + 1),
-            p(x, y + 1),
-            p(x + 1, y + 1),
-        ]
-        .into_iter()
+        self.neighbours(p, Stencil::EIGHT, Torus)
     }
 
     /// Up down left right neighbours
     pub fn neighbours4_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| (self.try_get(pt(x, y)), Point::new(x, y));
-        [p(x, y - 1), p(x - 1, y), p(x + 1, y), p(x, y + 1)]
-            .into_iter()
-            .filter_map(|(nei, p)| nei.map(|nei| (nei, p)))
+        self.neighbours(p, Stencil::FOUR, Bounded)
     }
 
     /// Up down left right neighbours, like a torus.
     pub fn neighbours4_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| self.wrapping_get(pt(x, y));
-        [p(x, y - 1), p(x - 1, y), p(x + 1, y), p(x, y + 1)].into_iter()
+        self.neighbours(p, Stencil::FOUR, Torus)
     }
 
     // Direct diagonals from thiss point.
     pub fn diagonals_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| (self.try_get(pt(x, y)), Point::new(x, y));
-        [
-            p(x - 1, y - 1),
-            p(x + 1, y - 1),
-            p(x - 1, y + 1),
-            p(x + 1, y + 1),
-        ]
-        .into_iter()
-        .filter_map(|(nei, p)| nei.map(|nei| (nei, p)))
+        self.neighbours(p, Stencil::DIAGONALS, Bounded)
     }
 
     /// Return neighbours as if the field is the surface of a torus.
     pub fn diagonals_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
-        let Point { x, y } = p;
-        let p = |x, y| self.wrapping_get(pt(x, y));
-
-        [
-            p(x - 1, y - 1),
-            p(x + 1, y - 1),
-            p(x - 1, y + 1),
-            p(x + 1, y + 1),
-        ]
-        .into_iter()
+        self.neighbours(p, Stencil::DIAGONALS, Torus)
     }
 
     pub fn points(&self) -> PointsIter {
@@ -240,33 +241,250 @@ impl<T: From<u8>> DenseField<T> {
     }
 
     pub fn from_lines_with(lines: Vec<String>, f: fn(u8) -> T) -> Self {
-        let width = lines[0].len() as isize;
-        let height = lines.len() as isize;
+        Self::from_lines_with_p(lines, |c, _| f(c))
+    }
+
+    /// Create a field from line input, returning an error rather than
+    /// panicking. See `try_from_lines_with_p`.
+    pub fn try_from_lines(lines: Vec<String>, leniency: Leniency) -> Result<Self, FieldError> {
+        Self::try_from_lines_with_p(lines, leniency, |c, _| T::from(c))
+    }
 
-        for line in &lines {
-            assert_eq!(line.len(), width as usize);
+    /// Same as `from_lines_with_markers`, but converting cells with From<u8>.
+    pub fn from_lines_markers(lines: Vec<String>, markers: &[(u8, u8)]) -> (Self, Markers) {
+        Self::from_lines_with_markers(lines, markers, |c, _| T::from(c))
+    }
+}
+
+impl<T> DenseField<T> {
+    /// Create a field from line input, passing the point of each cell to the
+    /// closure as well as the byte. Since it's a closure you can mutate local
+    /// state while parsing, such as remembering where the start is.
+    pub fn from_lines_with_p(lines: Vec<String>, f: impl FnMut(u8, IPoint) -> T) -> Self {
+        Self::try_from_lines_with_p(lines, Leniency::default(), f).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Create a field from line input, returning an error rather than
+    /// panicking if the lines can't make a field. The leniency allows ragged
+    /// rows to be padded and blank edge columns to be trimmed, so that inputs
+    /// that aren't quite rectangular can still be parsed.
+    pub fn try_from_lines_with_p(
+        lines: Vec<String>,
+        leniency: Leniency,
+        mut f: impl FnMut(u8, IPoint) -> T,
+    ) -> Result<Self, FieldError> {
+        if let Some(line) = lines.iter().position(|l| !l.is_ascii()) {
+            return Err(FieldError::NotAscii { line });
         }
 
-        let data = lines.join("");
-        assert!(data.is_ascii());
-        let data = data.into_bytes().into_iter().map(f).collect();
+        let rows: Vec<&[u8]> = lines.iter().map(|l| l.as_bytes()).collect();
+        let longest = rows.iter().map(|r| r.len()).max().unwrap_or(0);
 
-        DenseField {
-            width,
-            height,
+        // Missing cells off the end of short rows count as blank too.
+        let is_blank = |x: usize| {
+            rows.iter()
+                .all(|r| r.get(x).is_none_or(|c| c.is_ascii_whitespace()))
+        };
+
+        let first = if leniency.trim_leading {
+            (0..longest).find(|x| !is_blank(*x)).unwrap_or(longest)
+        } else {
+            0
+        };
+        let last = if leniency.trim_trailing {
+            (first..longest)
+                .rev()
+                .find(|x| !is_blank(*x))
+                .map_or(first, |x| x + 1)
+        } else {
+            longest
+        };
+
+        let width = last - first;
+        if width == 0 || rows.is_empty() {
+            return Err(FieldError::Empty);
+        }
+
+        let mut data = Vec::with_capacity(width * rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            let row = &row[first.min(row.len())..last.min(row.len())];
+            if row.len() < width && leniency.pad.is_none() {
+                return Err(FieldError::Ragged {
+                    line: y,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+
+            for x in 0..width {
+                let c = row.get(x).copied().or(leniency.pad).unwrap();
+                data.push(f(c, pt(x as isize, y as isize)));
+            }
+        }
+
+        Ok(DenseField {
+            width: width as isize,
+            height: rows.len() as isize,
             data,
+        })
+    }
+
+    /// Create a field from line input, recording the position of marker cells
+    /// such as `S` and `E`. Each marker is given as `(marker, replacement)`,
+    /// and the closure sees the replacement byte rather than the marker. Use
+    /// the same byte for both to keep the marker in the field.
+    pub fn from_lines_with_markers(
+        lines: Vec<String>,
+        markers: &[(u8, u8)],
+        mut f: impl FnMut(u8, IPoint) -> T,
+    ) -> (Self, Markers) {
+        let mut found = Markers::default();
+        let field = Self::from_lines_with_p(lines, |c, p| {
+            match markers.iter().find(|(marker, _)| *marker == c) {
+                Some(&(marker, replacement)) => {
+                    found.points.entry(marker).or_default().push(p);
+                    f(replacement, p)
+                }
+                None => f(c, p),
+            }
+        });
+
+        (field, found)
+    }
+}
+
+/// How forgiving to be when creating a field from lines. The default is
+/// strict, requiring every line to be the same length.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Leniency {
+    /// Pad short rows out to the longest row with this byte.
+    pub pad: Option<u8>,
+    /// Remove columns on the left that are whitespace in every row.
+    pub trim_leading: bool,
+    /// Remove columns on the right that are whitespace in every row. Short
+    /// rows count as whitespace past their end.
+    pub trim_trailing: bool,
+}
+
+impl Leniency {
+    /// Pad short rows with the given byte, no trimming.
+    pub fn padded(pad: u8) -> Self {
+        Leniency {
+            pad: Some(pad),
+            ..Default::default()
         }
     }
+}
 
-    // TODO: Would allow mutating local variables as we create the field, such
-    // as for finding the start part, without making the cell type actually
-    // understand start points.
-    // pub fn from_lines_with_p(lines: Vec<String>, f: fn(u8, IPoint) -> T) -> Self {
-    //     let bf = Self::from_lines(lines);
-    //     let mut f = DenseField::<T>::new();
+/// Reasons that lines could not be made into a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// There were no lines, or no columns were left after trimming.
+    Empty,
+    /// A line contained non-ASCII data, so bytes wouldn't map to cells.
+    NotAscii { line: usize },
+    /// A line was shorter than the widest line and no padding was given.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldError::Empty => write!(f, "field has no cells"),
+            FieldError::NotAscii { line } => write!(f, "line {line} is not ascii"),
+            FieldError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line} has length {found}, expected {expected}"),
+        }
+    }
+}
+
+impl Error for FieldError {}
+
+/// A cell that is parsed from and displayed as a single byte. Usually derived
+/// with `#[derive(GridCell)]`, which also implements `From<u8>` and `Display`
+/// in terms of this trait.
+///
+/// The fallible conversion lives here rather than in `TryFrom<u8>`, since that
+/// would conflict with the blanket implementation that comes with `From<u8>`.
+pub trait GridCell: Sized {
+    /// Every byte that can be parsed into a cell.
+    const BYTES: &'static [u8];
+
+    fn try_from_byte(byte: u8) -> Result<Self, UnknownCell>;
 
-    //     for p in bf.points() {}
-    // }
+    /// The byte this cell is displayed as.
+    fn to_byte(&self) -> u8;
+}
+
+/// Error for a byte that doesn't correspond to any cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCell {
+    pub byte: u8,
+    /// Name of the cell type we were trying to parse.
+    pub cell: &'static str,
+    /// Bytes that would have been valid.
+    pub expected: &'static [u8],
+}
+
+impl Display for UnknownCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown {} '{}' ({:#04x}), expected one of ",
+            self.cell,
+            self.byte.escape_ascii(),
+            self.byte
+        )?;
+        for (i, b) in self.expected.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "'{}'", b.escape_ascii())?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UnknownCell {}
+
+/// Positions of marker cells found while parsing a field, keyed by the marker
+/// byte. Points for each marker are in row major order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Markers {
+    points: HashMap<u8, Vec<IPoint>>,
+}
+
+impl Markers {
+    /// Get the position of a marker that should appear exactly once. Panics
+    /// if it was missing or appeared multiple times.
+    pub fn one(&self, marker: u8) -> IPoint {
+        match self.all(marker) {
+            [p] => *p,
+            ps => panic!(
+                "expected one '{}' marker, found {}",
+                marker as char,
+                ps.len()
+            ),
+        }
+    }
+
+    /// Get the position of a marker if it appeared at all. If it appeared
+    /// multiple times this is the first in row major order.
+    pub fn get(&self, marker: u8) -> Option<IPoint> {
+        self.all(marker).first().copied()
+    }
+
+    /// All positions of a marker, which is empty if it never appeared.
+    pub fn all(&self, marker: u8) -> &[IPoint] {
+        self.points.get(&marker).map(Vec::as_slice).unwrap_or(&[])
+    }
 }
 
 #[derive(Debug)]
@@ -292,7 +510,7 @@ impl Iterator for PointsIter {
 
 #[cfg(test)]
 mod test {
-    use crate::two::{pt, DenseField};
+    use crate::two::{DenseField, FieldError, GridCell, Leniency, UnknownCell, pt};
     use std::{collections::HashSet, mem};
 
     #[test]
@@ -369,4 +587,121 @@ mod test {
 
         assert!(ps.into_iter().eq(DenseField::new(5, 10, 0).points()));
     }
+
+    #[test]
+    fn markers() {
+        let input = vec!["#S.".to_string(), "..E".to_string(), "E.#".to_string()];
+        let (field, markers) =
+            DenseField::<u8>::from_lines_markers(input, &[(b'S', b'.'), (b'E', b'E')]);
+
+        assert_eq!(markers.one(b'S'), pt(1, 0));
+        assert_eq!(markers.all(b'E'), &[pt(2, 1), pt(0, 2)]);
+        assert_eq!(markers.get(b'X'), None);
+        assert_eq!(*field.get(pt(1, 0)), b'.');
+        assert_eq!(*field.get(pt(2, 1)), b'E');
+    }
+
+    #[test]
+    fn with_p() {
+        let field =
+            DenseField::from_lines_with_p(vec!["ab".to_string(), "cd".to_string()], |c, p| (c, p));
+        assert!(field.points().all(|p| field.get(p).1 == p));
+        assert_eq!(field.get(pt(0, 1)).0, b'c');
+    }
+
+    #[test]
+    fn lenient() {
+        let lines = |ls: &[&str]| ls.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        let ragged = lines(&["  ..#", "  .", "  #.#.  "]);
+        assert_eq!(
+            DenseField::<u8>::try_from_lines(ragged.clone(), Leniency::default()),
+            Err(FieldError::Ragged {
+                line: 0,
+                expected: 8,
+                found: 5
+            })
+        );
+
+        let field = DenseField::<u8>::try_from_lines(
+            ragged.clone(),
+            Leniency {
+                pad: Some(b' '),
+                trim_leading: true,
+                trim_trailing: true,
+            },
+        )
+        .unwrap();
+        assert_eq!((field.width(), field.height()), (4, 3));
+        assert_eq!(field.data(), b"..# .   #.#.");
+
+        let field = DenseField::<u8>::try_from_lines(ragged, Leniency::padded(b'x')).unwrap();
+        assert_eq!(field.width(), 8);
+        assert_eq!(*field.get(pt(7, 1)), b'x');
+
+        assert_eq!(
+            DenseField::<u8>::try_from_lines(vec![], Leniency::default()),
+            Err(FieldError::Empty)
+        );
+        assert_eq!(
+            DenseField::<u8>::try_from_lines(lines(&["ab", "\u{e9}"]), Leniency::padded(b' ')),
+            Err(FieldError::NotAscii { line: 1 })
+        );
+    }
+
+    #[test]
+    fn derive_cell() {
+        #[derive(Debug, Clone, PartialEq, GridCell)]
+        enum Cell {
+            #[cell(b'#')]
+            Wall,
+            #[cell(b'.', ' ')]
+            Empty { visited: bool },
+            #[cell(b'|')]
+            Beam(usize),
+        }
+
+        let field = DenseField::<Cell>::from_lines(vec!["#. |".to_string()]);
+        assert_eq!(*field.get(pt(0, 0)), Cell::Wall);
+        assert_eq!(*field.get(pt(2, 0)), Cell::Empty { visited: false });
+        assert_eq!(*field.get(pt(3, 0)), Cell::Beam(0));
+
+        let shown: String = field.data().iter().map(|c| c.to_string()).collect();
+        assert_eq!(shown, "#..|");
+
+        assert_eq!(
+            Cell::try_from_byte(b' '),
+            Ok(Cell::Empty { visited: false })
+        );
+        assert_eq!(Cell::Beam(3).to_byte(), b'|');
+
+        let err = Cell::try_from_byte(b'x').unwrap_err();
+        assert_eq!(
+            err,
+            UnknownCell {
+                byte: b'x',
+                cell: "Cell",
+                expected: b"#. |"
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "unknown Cell 'x' (0x78), expected one of '#', '.', ' ', '|'"
+        );
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let field = DenseField::<u8>::from_lines(vec!["abc".to_string(), "def".to_string()]);
+        assert_eq!(field.row(1), b"def");
+        assert_eq!(field.rows().collect::<Vec<_>>(), vec![b"abc", b"def"]);
+        assert!(field.column(2).eq(b"cf"));
+
+        let columns: Vec<Vec<u8>> = field.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, vec![b"ad", b"be", b"cf"]);
+
+        let mut field = field;
+        field.row_mut(0).copy_from_slice(b"xyz");
+        assert_eq!(*field.get(pt(2, 0)), b'z');
+    }
 }
@@ -0,0 +1,183 @@
+use std::{cmp::Reverse, collections::VecDeque};
+
+use crate::two::{DenseField, IPoint, Stencil, Topology, pt};
+
+/// A shortcut between two cells that skips the path between them, see
+/// `DenseField::cheats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cheat {
+    pub from: IPoint,
+    pub to: IPoint,
+    /// How much shorter the whole route is by taking the cheat.
+    pub saved: usize,
+}
+
+impl<T> DenseField<T> {
+    /// Number of up/down/left/right steps to each cell from the nearest of
+    /// the sources, only stepping on cells that are passable. Cells that
+    /// can't be reached are None. The sources don't need to be passable.
+    pub fn distances_from(
+        &self,
+        sources: impl IntoIterator<Item = IPoint>,
+        passable: impl Fn(&T) -> bool,
+        topology: impl Topology,
+    ) -> DenseField<Option<usize>> {
+        let mut dist = DenseField {
+            width: self.width,
+            height: self.height,
+            data: vec![None; self.data.len()],
+        };
+        let mut q = VecDeque::new();
+        for source in sources {
+            if dist.get(source).is_none() {
+                *dist.get_mut(source) = Some(0);
+                q.push_back((source, 0));
+            }
+        }
+
+        while let Some((p, d)) = q.pop_front() {
+            for (cell, n) in self.neighbours(p, Stencil::FOUR, &topology) {
+                if passable(cell) && dist.get(n).is_none() {
+                    *dist.get_mut(n) = Some(d + 1);
+                    q.push_back((n, d + 1));
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Every ordered pair of different points in the field that are at most
+    /// `radius` apart by taxicab distance, along with that distance.
+    pub fn pairs_within(
+        &self,
+        radius: usize,
+    ) -> impl Iterator<Item = (IPoint, IPoint, usize)> + '_ {
+        let r = radius as isize;
+        self.points().flat_map(move |a| {
+            (-r..=r).flat_map(move |dy| {
+                let rx = r - dy.abs();
+                (-rx..=rx).filter_map(move |dx| {
+                    let b = a + pt(dx, dy);
+                    let d = a.taxicab_dist(b);
+                    (d != 0 && self.try_get(b).is_some()).then_some((a, b, d))
+                })
+            })
+        })
+    }
+}
+
+impl DenseField<Option<usize>> {
+    /// Given distances from the start (self) and to the end, find every way
+    /// of jumping from one reachable cell to another at most `radius` away
+    /// that makes the route shorter. A jump costs its taxicab distance. The
+    /// biggest savings come first.
+    pub fn cheats(&self, to_end: &DenseField<Option<usize>>, radius: usize) -> Vec<Cheat> {
+        let Some(best) = self
+            .data
+            .iter()
+            .zip(&to_end.data)
+            .filter_map(|(a, b)| Some((*a)? + (*b)?))
+            .min()
+        else {
+            return vec![];
+        };
+
+        let mut cheats = self
+            .pairs_within(radius)
+            .filter_map(|(from, to, d)| {
+                let total = (*self.get(from))? + d + (*to_end.get(to))?;
+                (total < best).then(|| Cheat {
+                    from,
+                    to,
+                    saved: best - total,
+                })
+            })
+            .collect::<Vec<_>>();
+        cheats.sort_by_key(|c| Reverse(c.saved));
+        cheats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{Bounded, DenseField, Torus, pt};
+
+    fn racetrack() -> DenseField<u8> {
+        DenseField::from_lines(
+            [
+                "###############",
+                "#...#...#.....#",
+                "#.#.#.#.#.###.#",
+                "#S#...#.#.#...#",
+                "#######.#.#.###",
+                "#######.#.#...#",
+                "#######.#.###.#",
+                "###..E#...#...#",
+                "###.#######.###",
+                "#...###...#...#",
+                "#.#####.#.###.#",
+                "#.#...#.#.#...#",
+                "#.#.#.#.#.#.###",
+                "#...#...#...###",
+                "###############",
+            ]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn distances() {
+        let field = racetrack();
+        let start = field.find(&b'S').unwrap();
+        let end = field.find(&b'E').unwrap();
+
+        let from_start = field.distances_from([start], |c| *c != b'#', Bounded);
+        assert_eq!(*from_start.get(start), Some(0));
+        assert_eq!(*from_start.get(end), Some(84));
+        assert_eq!(*from_start.get(pt(0, 0)), None);
+
+        // Two sources, whichever is nearer wins.
+        let both = field.distances_from([start, end], |c| *c != b'#', Bounded);
+        assert_eq!(*both.get(end), Some(0));
+        assert_eq!(*both.get(pt(1, 1)), Some(2));
+        assert_eq!(*both.get(pt(3, 7)), Some(2));
+
+        let open = DenseField::new(5, 3, b'.');
+        let wrapped = open.distances_from([pt(0, 0)], |_| true, Torus);
+        assert_eq!(*wrapped.get(pt(4, 2)), Some(2));
+    }
+
+    #[test]
+    fn pairs() {
+        let field = DenseField::new(5, 5, 0);
+        assert_eq!(field.pairs_within(0).count(), 0);
+        // Corners have 2 neighbours, edges 3 and the middle 4.
+        assert_eq!(field.pairs_within(1).count(), 4 * 2 + 12 * 3 + 9 * 4);
+        assert!(
+            field
+                .pairs_within(2)
+                .all(|(a, b, d)| a.taxicab_dist(b) == d && d <= 2)
+        );
+    }
+
+    #[test]
+    fn cheats() {
+        let field = racetrack();
+        let start = field.find(&b'S').unwrap();
+        let end = field.find(&b'E').unwrap();
+        let from_start = field.distances_from([start], |c| *c != b'#', Bounded);
+        let to_end = field.distances_from([end], |c| *c != b'#', Bounded);
+
+        let short = from_start.cheats(&to_end, 2);
+        assert_eq!(short.len(), 44);
+        assert_eq!(short[0].saved, 64);
+        assert_eq!(short.iter().filter(|c| c.saved == 2).count(), 14);
+
+        let long = from_start.cheats(&to_end, 20);
+        assert_eq!(long.iter().take_while(|c| c.saved >= 50).count(), 285);
+        assert_eq!(long.iter().filter(|c| c.saved == 76).count(), 3);
+    }
+}
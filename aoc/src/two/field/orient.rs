@@ -0,0 +1,194 @@
+use crate::two::DenseField;
+
+/// Re-orienting a field in place. These move cells around within the existing
+/// storage rather than cloning the field.
+impl<T> DenseField<T> {
+    /// Swap rows and columns, so the cell at (x, y) moves to (y, x).
+    pub fn transpose(&mut self) {
+        self.permute(self.height, self.width, |x, y| (y, x));
+    }
+
+    /// Rotate the field by 90 degrees clockwise. This obviously flips the
+    /// height and width if they're different.
+    pub fn rotate_clockwise(&mut self) {
+        let h = self.height;
+        self.permute(self.height, self.width, |x, y| (h - y - 1, x));
+    }
+
+    /// Rotate the field by 90 degrees anticlockwise.
+    pub fn rotate_anticlockwise(&mut self) {
+        let (w, h) = (self.width, self.height);
+        self.permute(h, w, |x, y| (y, w - x - 1));
+    }
+
+    /// Rotate the field by 180 degrees. Dimensions stay the same.
+    pub fn rotate_180(&mut self) {
+        self.data.reverse();
+    }
+
+    /// Mirror left to right, so each row is reversed.
+    pub fn flip_horizontal(&mut self) {
+        for row in self.data.chunks_exact_mut(self.width as usize) {
+            row.reverse();
+        }
+    }
+
+    /// Mirror top to bottom, so the order of rows is reversed.
+    pub fn flip_vertical(&mut self) {
+        let w = self.width as usize;
+        let h = self.height as usize;
+        for y in 0..h / 2 {
+            let (top, bottom) = self.data.split_at_mut((h - y - 1) * w);
+            top[y * w..(y + 1) * w].swap_with_slice(&mut bottom[..w]);
+        }
+    }
+
+    /// All eight orientations of the field: the four rotations, then the four
+    /// rotations of the horizontally flipped field. The first is the field as
+    /// it is now.
+    pub fn symmetries(&self) -> Symmetries<T>
+    where
+        T: Clone,
+    {
+        Symmetries {
+            field: self.clone(),
+            step: 0,
+        }
+    }
+
+    /// Move every cell to a new position given by `dest`, changing the
+    /// dimensions to `new_width` by `new_height`. `dest` must be a bijection.
+    ///
+    /// This follows each cycle of the permutation, swapping cells into place,
+    /// so only a byte per cell of extra memory is needed.
+    fn permute(
+        &mut self,
+        new_width: isize,
+        new_height: isize,
+        dest: impl Fn(isize, isize) -> (isize, isize),
+    ) {
+        assert_eq!(new_width * new_height, self.width * self.height);
+        let old_width = self.width;
+        let dest = |i: usize| {
+            let (x, y) = dest(i as isize % old_width, i as isize / old_width);
+            (y * new_width + x) as usize
+        };
+
+        let mut done = vec![false; self.data.len()];
+        for start in 0..self.data.len() {
+            if done[start] {
+                continue;
+            }
+
+            // data[start] always holds the cell that was originally at
+            // `current`, so we swap it straight to where it belongs.
+            let mut current = start;
+            loop {
+                done[current] = true;
+                let d = dest(current);
+                if d == start {
+                    break;
+                }
+                self.data.swap(start, d);
+                current = d;
+            }
+        }
+
+        self.width = new_width;
+        self.height = new_height;
+    }
+}
+
+/// Iterator over the eight orientations of a field, see
+/// `DenseField::symmetries`.
+#[derive(Debug)]
+pub struct Symmetries<T> {
+    field: DenseField<T>,
+    step: usize,
+}
+
+impl<T: Clone> Iterator for Symmetries<T> {
+    type Item = DenseField<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step {
+            0 => {}
+            4 => {
+                // Undo the last rotation to get back to the original, then
+                // flip to get the mirror image.
+                self.field.rotate_clockwise();
+                self.field.flip_horizontal();
+            }
+            1..8 => self.field.rotate_clockwise(),
+            _ => return None,
+        }
+        self.step += 1;
+        Some(self.field.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::two::DenseField;
+
+    fn numbered(width: isize, height: isize) -> DenseField<isize> {
+        let mut field = DenseField::new(width, height, 0);
+        for (i, p) in field.points().enumerate() {
+            *field.get_mut(p) = i as isize;
+        }
+        field
+    }
+
+    fn rows(field: &DenseField<isize>) -> Vec<Vec<isize>> {
+        field.rows().map(|r| r.to_vec()).collect()
+    }
+
+    #[test]
+    fn orientations() {
+        let field = numbered(3, 2);
+
+        let mut f = field.clone();
+        f.transpose();
+        assert_eq!(rows(&f), vec![vec![0, 3], vec![1, 4], vec![2, 5]]);
+
+        let mut f = field.clone();
+        f.rotate_clockwise();
+        assert_eq!(rows(&f), vec![vec![3, 0], vec![4, 1], vec![5, 2]]);
+
+        let mut f = field.clone();
+        f.rotate_anticlockwise();
+        assert_eq!(rows(&f), vec![vec![2, 5], vec![1, 4], vec![0, 3]]);
+        f.rotate_clockwise();
+        assert_eq!(f, field);
+
+        let mut f = field.clone();
+        f.rotate_180();
+        assert_eq!(rows(&f), vec![vec![5, 4, 3], vec![2, 1, 0]]);
+
+        let mut f = field.clone();
+        f.flip_horizontal();
+        assert_eq!(rows(&f), vec![vec![2, 1, 0], vec![5, 4, 3]]);
+
+        let mut f = numbered(2, 3);
+        f.flip_vertical();
+        assert_eq!(rows(&f), vec![vec![4, 5], vec![2, 3], vec![0, 1]]);
+    }
+
+    #[test]
+    fn symmetries() {
+        let field = numbered(4, 3);
+        let all: Vec<_> = field.symmetries().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], field);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), 8);
+
+        let mut flipped = field.clone();
+        flipped.flip_vertical();
+        assert!(all.contains(&flipped));
+        let mut transposed = field.clone();
+        transposed.transpose();
+        assert!(all.contains(&transposed));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::two::{DenseField, IPoint, pt};
+
+/// A small field to search for with `DenseField::find_pattern`. None cells
+/// are wildcards that match anything. Can hold several orientations of the
+/// same pattern to match any of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern<T> {
+    orientations: Vec<DenseField<Option<T>>>,
+}
+
+/// Where a pattern matched, as the top left of the matched area, and which
+/// of the pattern's orientations matched there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatternMatch {
+    pub at: IPoint,
+    pub orientation: usize,
+}
+
+impl<T: Clone + PartialEq> Pattern<T> {
+    /// Match the cells exactly as they are.
+    pub fn new(cells: DenseField<Option<T>>) -> Self {
+        Pattern {
+            orientations: vec![cells],
+        }
+    }
+
+    /// Match any rotation or reflection of the cells. Symmetric patterns are
+    /// only kept once per distinct orientation, so a match isn't found twice.
+    pub fn all_orientations(cells: DenseField<Option<T>>) -> Self {
+        let mut orientations: Vec<DenseField<Option<T>>> = vec![];
+        for o in cells.symmetries() {
+            if !orientations.contains(&o) {
+                orientations.push(o);
+            }
+        }
+        Pattern { orientations }
+    }
+
+    /// Each orientation this pattern matches, indexed by
+    /// `PatternMatch::orientation`.
+    pub fn orientations(&self) -> &[DenseField<Option<T>>] {
+        &self.orientations
+    }
+}
+
+impl Pattern<u8> {
+    /// Read a pattern from lines, treating `wildcard` as matching anything.
+    pub fn from_lines(lines: Vec<String>, wildcard: u8) -> Self {
+        Self::new(DenseField::from_lines_with_p(lines, |c, _| {
+            (c != wildcard).then_some(c)
+        }))
+    }
+
+    /// Read a pattern from lines, matching any orientation. See
+    /// `from_lines` and `all_orientations`.
+    pub fn from_lines_all_orientations(lines: Vec<String>, wildcard: u8) -> Self {
+        let pattern = Self::from_lines(lines, wildcard);
+        Self::all_orientations(pattern.orientations.into_iter().next().unwrap())
+    }
+}
+
+/// Runs of non wildcard cells along each row of a pattern, so that a whole
+/// run can be compared as a slice.
+struct Run<T> {
+    offset: IPoint,
+    cells: Vec<T>,
+}
+
+fn runs<T: Clone>(pattern: &DenseField<Option<T>>) -> Vec<Run<T>> {
+    let mut runs = vec![];
+    for (y, row) in pattern.rows().enumerate() {
+        let mut x = 0;
+        for chunk in row.split(|c| c.is_none()) {
+            if !chunk.is_empty() {
+                runs.push(Run {
+                    offset: pt(x as isize, y as isize),
+                    cells: chunk.iter().map(|c| c.clone().unwrap()).collect(),
+                });
+            }
+            x += chunk.len() + 1;
+        }
+    }
+    runs
+}
+
+impl<T: Clone + PartialEq> DenseField<T> {
+    /// Find everywhere the pattern matches in this field, in row major order
+    /// for each orientation. Matches have to fit entirely inside the field.
+    pub fn find_pattern(&self, pattern: &Pattern<T>) -> Vec<PatternMatch> {
+        let mut matches = vec![];
+        for (orientation, cells) in pattern.orientations.iter().enumerate() {
+            let runs = runs(cells);
+            for y in 0..=self.height - cells.height() {
+                for x in 0..=self.width - cells.width() {
+                    let fits = runs.iter().all(|run| {
+                        let row = self.row(y + run.offset.y);
+                        let start = (x + run.offset.x) as usize;
+                        row[start..start + run.cells.len()] == run.cells[..]
+                    });
+                    if fits {
+                        matches.push(PatternMatch {
+                            at: pt(x, y),
+                            orientation,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DenseField, Pattern, PatternMatch, pt};
+
+    fn lines(s: &[&str]) -> Vec<String> {
+        s.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn wildcards() {
+        let field = DenseField::<u8>::from_lines(lines(&["abab", "cdcd", "abxb"]));
+        let pattern = Pattern::from_lines(lines(&["a?", "?d"]), b'?');
+        let found: Vec<_> = field.find_pattern(&pattern).iter().map(|m| m.at).collect();
+        assert_eq!(found, vec![pt(0, 0), pt(2, 0)]);
+
+        // Too big to fit anywhere.
+        let pattern = Pattern::from_lines(lines(&["?????"]), b'?');
+        assert!(field.find_pattern(&pattern).is_empty());
+    }
+
+    #[test]
+    fn orientations() {
+        // X-MAS from 2024 day 4.
+        let field = DenseField::<u8>::from_lines(lines(&[
+            ".M.S......",
+            "..A..MSMS.",
+            ".M.S.MAA..",
+            "..A.ASMSM.",
+            ".M.S.M....",
+            "..........",
+            "S.S.S.S.S.",
+            ".A.A.A.A..",
+            "M.M.M.M.M.",
+            "..........",
+        ]));
+        let pattern = Pattern::from_lines_all_orientations(lines(&["M.S", ".A.", "M.S"]), b'.');
+        assert_eq!(pattern.orientations().len(), 4);
+
+        let found = field.find_pattern(&pattern);
+        assert_eq!(found.len(), 9);
+        assert!(found.contains(&PatternMatch {
+            at: pt(1, 0),
+            orientation: 0
+        }));
+
+        // Fully symmetric patterns only have one orientation.
+        let pattern = Pattern::from_lines_all_orientations(lines(&["S.S", ".A.", "S.S"]), b'.');
+        assert_eq!(pattern.orientations().len(), 1);
+    }
+}
@@ -0,0 +1,88 @@
+use std::iter;
+
+use crate::two::{DenseField, IPoint, Stencil};
+
+impl<T> DenseField<T> {
+    /// Walk from `p` in steps of `dir` until leaving the field, yielding each
+    /// point and its cell. The start point itself isn't included.
+    pub fn ray(&self, p: IPoint, dir: IPoint) -> impl Iterator<Item = (IPoint, &T)> {
+        assert!(dir != IPoint::new(0, 0), "ray needs a direction");
+        iter::successors(Some(p + dir), move |q| Some(*q + dir))
+            .map_while(|q| self.try_get(q).map(|v| (q, v)))
+    }
+
+    /// Like `ray` but wrapping around the edges like a torus. Stops when it
+    /// gets back round to `p`, which isn't included either.
+    pub fn ray_torus(&self, p: IPoint, dir: IPoint) -> impl Iterator<Item = (IPoint, &T)> {
+        assert!(dir != IPoint::new(0, 0), "ray needs a direction");
+        let start = self.wrapping_get(p).1;
+        iter::successors(Some(self.wrapping_get(p + dir).1), move |q| {
+            Some(self.wrapping_get(*q + dir).1)
+        })
+        .take_while(move |q| *q != start)
+        .map(|q| (q, self.get(q)))
+    }
+
+    /// Like `ray` but also stops at the first cell that `stop` returns true
+    /// for, eg a wall or a taller tree. That cell is still included, so the
+    /// last item says what the ray hit.
+    pub fn ray_until(
+        &self,
+        p: IPoint,
+        dir: IPoint,
+        stop: impl Fn(&T) -> bool,
+    ) -> impl Iterator<Item = (IPoint, &T)> {
+        self.ray(p, dir).scan(false, move |hit, (q, v)| {
+            if *hit {
+                return None;
+            }
+            *hit = stop(v);
+            Some((q, v))
+        })
+    }
+
+    /// Rays from `p` in all eight directions, in the same order as
+    /// `Stencil::EIGHT`.
+    pub fn rays8(&self, p: IPoint) -> impl Iterator<Item = impl Iterator<Item = (IPoint, &T)>> {
+        Stencil::EIGHT
+            .offsets()
+            .iter()
+            .map(move |dir| self.ray(p, *dir))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DOWN, DenseField, LEFT, RIGHT, UP, pt};
+
+    #[test]
+    fn rays() {
+        let field = DenseField::<u8>::from_lines(
+            ["abcd", "efgh", "ijkl"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        );
+        let text = |ray: &mut dyn Iterator<Item = (_, &u8)>| {
+            ray.map(|(_, c)| *c as char).collect::<String>()
+        };
+
+        assert_eq!(text(&mut field.ray(pt(0, 0), RIGHT)), "bcd");
+        assert_eq!(text(&mut field.ray(pt(0, 0), UP)), "");
+        assert_eq!(text(&mut field.ray(pt(0, 0), pt(2, 1))), "g");
+        assert_eq!(field.ray(pt(1, 1), DOWN).last(), Some((pt(1, 2), &b'j')));
+
+        assert_eq!(text(&mut field.ray_torus(pt(1, 1), LEFT)), "ehg");
+        assert_eq!(
+            text(&mut field.ray_torus(pt(0, 0), pt(1, 1))),
+            "fkdejchibgl"
+        );
+        assert_eq!(
+            text(&mut field.ray_until(pt(0, 1), RIGHT, |c| *c == b'g')),
+            "fg"
+        );
+
+        let all: Vec<String> = field.rays8(pt(1, 1)).map(|mut r| text(&mut r)).collect();
+        assert_eq!(all, ["a", "b", "c", "e", "gh", "i", "j", "k"]);
+    }
+}
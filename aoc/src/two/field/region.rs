@@ -0,0 +1,163 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::two::{Bounded, DenseField, IPoint, Stencil, Topology, pt};
+
+/// Statistics about a connected region of a field, see
+/// `DenseField::label_regions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// The first point of the region in row major order.
+    pub start: IPoint,
+    /// Number of cells in the region.
+    pub area: usize,
+    /// Number of cell edges between the region and anything else, including
+    /// the edge of the field.
+    pub perimeter: usize,
+    /// Number of straight sides of the region's outline, including the
+    /// outlines of any holes.
+    pub sides: usize,
+}
+
+impl<T> DenseField<T> {
+    /// Find every point that can be reached from `start` by only stepping on
+    /// cells that match the predicate, moving by the stencil's offsets across
+    /// the topology's edges. The start is always included.
+    pub fn flood_fill(
+        &self,
+        start: IPoint,
+        predicate: impl Fn(&T) -> bool,
+        stencil: Stencil,
+        topology: impl Topology,
+    ) -> HashSet<IPoint> {
+        let mut seen = HashSet::from([start]);
+        let mut q = VecDeque::from([start]);
+
+        while let Some(p) = q.pop_front() {
+            for (cell, n) in self.neighbours(p, stencil, &topology) {
+                if predicate(cell) && seen.insert(n) {
+                    q.push_back(n);
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// Split the field into regions of up/down/left/right connected cells,
+    /// where neighbouring cells are in the same region if `eq` says so.
+    /// Returns a field of region ids, which index into the list of regions.
+    pub fn label_regions(&self, eq: impl Fn(&T, &T) -> bool) -> (DenseField<usize>, Vec<Region>) {
+        let mut labels = DenseField::new(self.width, self.height, usize::MAX);
+        let mut regions = vec![];
+        let mut q = VecDeque::new();
+
+        for start in self.points() {
+            if *labels.get(start) != usize::MAX {
+                continue;
+            }
+
+            let id = regions.len();
+            *labels.get_mut(start) = id;
+            q.push_back(start);
+
+            let mut area = 0;
+            let mut perimeter = 0;
+            while let Some(p) = q.pop_front() {
+                area += 1;
+                // Anything missing from the four neighbours is the edge of
+                // the field, which is part of the perimeter too.
+                let mut connected = 0;
+                for (cell, n) in self.neighbours(p, Stencil::FOUR, Bounded) {
+                    if eq(self.get(p), cell) {
+                        connected += 1;
+                        if *labels.get(n) == usize::MAX {
+                            *labels.get_mut(n) = id;
+                            q.push_back(n);
+                        }
+                    }
+                }
+                perimeter += 4 - connected;
+            }
+
+            regions.push(Region {
+                start,
+                area,
+                perimeter,
+                sides: 0,
+            });
+        }
+
+        // Now every cell is labelled, count sides by counting corners. Each
+        // cell looks at its four 2x2 quarters for a corner of its region.
+        let same = |p: IPoint, id: usize| labels.try_get(p) == Some(&id);
+        for p in labels.points() {
+            let id = *labels.get(p);
+            for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                let a = same(p + pt(dx, 0), id);
+                let b = same(p + pt(0, dy), id);
+                let c = same(p + pt(dx, dy), id);
+                let outer_corner = !a && !b;
+                let inner_corner = a && b && !c;
+                if outer_corner || inner_corner {
+                    regions[id].sides += 1;
+                }
+            }
+        }
+
+        (labels, regions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{Bounded, DenseField, Stencil, Torus, pt};
+
+    fn field(lines: &[&str]) -> DenseField<u8> {
+        DenseField::from_lines(lines.iter().map(|l| l.to_string()).collect())
+    }
+
+    #[test]
+    fn flood_fill() {
+        let f = field(&["..#..", "..#..", "###..", "....#"]);
+        let filled = f.flood_fill(pt(0, 0), |c| *c == b'.', Stencil::FOUR, Bounded);
+        assert_eq!(filled.len(), 4);
+
+        let filled = f.flood_fill(pt(4, 0), |c| *c == b'.', Stencil::FOUR, Bounded);
+        assert_eq!(filled.len(), 10);
+
+        // Wrapping around lets the left side reach the right.
+        let filled = f.flood_fill(pt(0, 0), |c| *c == b'.', Stencil::FOUR, Torus);
+        assert_eq!(filled.len(), 14);
+    }
+
+    #[test]
+    fn regions() {
+        // Examples from 2024 day 12.
+        let f = field(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        let (labels, regions) = f.label_regions(|a, b| a == b);
+        assert_eq!(regions.len(), 5);
+        assert_eq!(*labels.get(pt(3, 3)), *labels.get(pt(2, 1)));
+
+        let stats: Vec<_> = regions
+            .iter()
+            .map(|r| (*f.get(r.start), r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            stats,
+            vec![
+                (b'A', 4, 10, 4),
+                (b'B', 4, 8, 4),
+                (b'C', 4, 10, 8),
+                (b'D', 1, 4, 4),
+                (b'E', 3, 8, 4)
+            ]
+        );
+
+        let f = field(&["AAAAAA", "AAABBA", "AAABBA", "ABBAAA", "ABBAAA", "AAAAAA"]);
+        let (_, regions) = f.label_regions(|a, b| a == b);
+        let price: usize = regions.iter().map(|r| r.area * r.sides).sum();
+        assert_eq!(price, 368);
+        let price: usize = regions.iter().map(|r| r.area * r.perimeter).sum();
+        assert_eq!(price, 1184);
+    }
+}
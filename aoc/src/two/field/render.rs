@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Write},
+};
+
+use crate::two::{DenseField, IPoint, pt};
+
+/// Terminal colours for highlighting cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    fn ansi(self) -> &'static str {
+        match self {
+            Colour::Red => "\x1b[31m",
+            Colour::Green => "\x1b[32m",
+            Colour::Yellow => "\x1b[33m",
+            Colour::Blue => "\x1b[34m",
+            Colour::Magenta => "\x1b[35m",
+            Colour::Cyan => "\x1b[36m",
+            Colour::Grey => "\x1b[90m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Draws a field as text, one line per row, see `DenseField::render`. Use
+/// `to_string` to get it or print it directly.
+pub struct Render<'a, T, F> {
+    field: &'a DenseField<T>,
+    cell: F,
+    // What to draw instead at each point, later overlays win.
+    overlays: HashMap<IPoint, (Option<char>, Option<Colour>)>,
+    min: IPoint,
+    max: IPoint,
+    colour: bool,
+}
+
+impl<T> DenseField<T> {
+    /// Start drawing the field, turning each cell into a character.
+    pub fn render<F: Fn(&T) -> char>(&self, cell: F) -> Render<'_, T, F> {
+        Render {
+            field: self,
+            cell,
+            overlays: HashMap::new(),
+            min: pt(0, 0),
+            max: pt(self.width - 1, self.height - 1),
+            colour: true,
+        }
+    }
+}
+
+impl<T, F: Fn(&T) -> char> Render<'_, T, F> {
+    /// Colour the given points, keeping their characters.
+    pub fn overlay(mut self, points: impl IntoIterator<Item = IPoint>, colour: Colour) -> Self {
+        for p in points {
+            self.overlays.entry(p).or_default().1 = Some(colour);
+        }
+        self
+    }
+
+    /// Draw the given points as `ch` in a colour, eg to show a path.
+    pub fn overlay_with(
+        mut self,
+        points: impl IntoIterator<Item = IPoint>,
+        ch: char,
+        colour: Colour,
+    ) -> Self {
+        for p in points {
+            self.overlays.insert(p, (Some(ch), Some(colour)));
+        }
+        self
+    }
+
+    /// Only draw the part of the field between two corners, inclusive. It's
+    /// clipped to the field.
+    pub fn viewport(mut self, min: IPoint, max: IPoint) -> Self {
+        self.min = pt(min.x.max(0), min.y.max(0));
+        self.max = pt(
+            max.x.min(self.field.width - 1),
+            max.y.min(self.field.height - 1),
+        );
+        self
+    }
+
+    /// Leave out the colours, for comparing against plain strings.
+    pub fn plain(mut self) -> Self {
+        self.colour = false;
+        self
+    }
+}
+
+impl<T, F: Fn(&T) -> char> Display for Render<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in self.min.y..=self.max.y {
+            for x in self.min.x..=self.max.x {
+                let p = pt(x, y);
+                let (ch, colour) = self.overlays.get(&p).copied().unwrap_or_default();
+                let ch = ch.unwrap_or_else(|| (self.cell)(self.field.get(p)));
+                match colour.filter(|_| self.colour) {
+                    Some(colour) => write!(f, "{}{ch}{RESET}", colour.ansi())?,
+                    None => f.write_char(ch)?,
+                }
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+/// Draw two fields of the same size side by side, with the cells that differ
+/// in red on the left and green on the right. Handy for seeing what a step of
+/// a simulation did, or why a test failed.
+pub fn diff<T: PartialEq>(
+    a: &DenseField<T>,
+    b: &DenseField<T>,
+    cell: impl Fn(&T) -> char,
+) -> String {
+    assert_eq!((a.width, a.height), (b.width, b.height));
+    let changed = a
+        .points()
+        .filter(|p| a.get(*p) != b.get(*p))
+        .collect::<Vec<_>>();
+
+    let left = a
+        .render(&cell)
+        .overlay(changed.clone(), Colour::Red)
+        .to_string();
+    let right = b.render(&cell).overlay(changed, Colour::Green).to_string();
+    left.lines()
+        .zip(right.lines())
+        .map(|(l, r)| format!("{l} | {r}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{Colour, DenseField, diff, pt};
+
+    fn field() -> DenseField<u8> {
+        DenseField::from_lines(
+            ["#....", "#.##.", "....#"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn plain() {
+        let field = field();
+        let wall = |c: &u8| if *c == b'#' { '█' } else { ' ' };
+        assert_eq!(field.render(wall).to_string(), "█    \n█ ██ \n    █\n");
+
+        let path = [pt(1, 0), pt(2, 0), pt(3, 0), pt(4, 0), pt(4, 1)];
+        let drawn = field.render(wall).overlay_with(path, 'o', Colour::Green);
+        assert_eq!(drawn.plain().to_string(), "█oooo\n█ ██o\n    █\n");
+
+        let cropped = field
+            .render(|c| *c as char)
+            .viewport(pt(2, 1), pt(10, 10))
+            .to_string();
+        assert_eq!(cropped, "##.\n..#\n");
+    }
+
+    #[test]
+    fn colours() {
+        let field = field();
+        let drawn = field
+            .render(|c| *c as char)
+            .overlay([pt(0, 0)], Colour::Red)
+            .viewport(pt(0, 0), pt(1, 0))
+            .to_string();
+        assert_eq!(drawn, "\x1b[31m#\x1b[0m.\n");
+
+        // The last overlay wins.
+        let drawn = field
+            .render(|c| *c as char)
+            .overlay_with([pt(1, 0)], 'x', Colour::Red)
+            .overlay([pt(1, 0)], Colour::Blue)
+            .viewport(pt(0, 0), pt(1, 0))
+            .to_string();
+        assert_eq!(drawn, "#\x1b[34mx\x1b[0m\n");
+    }
+
+    #[test]
+    fn diffs() {
+        let a = field();
+        let mut b = field();
+        *b.get_mut(pt(1, 1)) = b'#';
+
+        let shown = diff(&a, &b, |c| *c as char);
+        let lines = shown.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "#.... | #....");
+        assert_eq!(lines[1], "#\x1b[31m.\x1b[0m##. | #\x1b[32m#\x1b[0m##.");
+        assert_eq!(lines[2], "....# | ....#");
+
+        assert_eq!(diff(&a, &a, |c| *c as char).lines().count(), 3);
+    }
+}
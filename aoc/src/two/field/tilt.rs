@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use crate::two::{DenseField, Dirn, IPoint, pt};
+
+/// What a cell does when something is pushed into it, see
+/// `DenseField::push_chain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Push {
+    /// Nothing here, so things can move into it.
+    Empty,
+    /// Can't move, so nothing pushed into it can either.
+    Blocked,
+    /// Part of an object that moves as one, made up of these cells including
+    /// this one, eg both halves of a wide box.
+    Moves(Vec<IPoint>),
+}
+
+impl<T> DenseField<T> {
+    /// Slide every movable cell as far as it'll go in a direction, until it
+    /// hits a blocker, another movable cell or the edge. Cells that are
+    /// neither are empty space that movable cells pass through.
+    pub fn tilt(
+        &mut self,
+        dir: Dirn,
+        is_movable: impl Fn(&T) -> bool,
+        is_blocker: impl Fn(&T) -> bool,
+    ) {
+        // Walk each line starting from the edge we're tilting towards,
+        // tracking where the next movable cell will come to rest.
+        let (lines, len) = match dir {
+            Dirn::Up | Dirn::Down => (self.width, self.height),
+            Dirn::Left | Dirn::Right => (self.height, self.width),
+        };
+        let point = |line, i| match dir {
+            Dirn::Up => pt(line, i),
+            Dirn::Down => pt(line, len - 1 - i),
+            Dirn::Left => pt(i, line),
+            Dirn::Right => pt(len - 1 - i, line),
+        };
+
+        for line in 0..lines {
+            let mut rest = 0;
+            for i in 0..len {
+                let cell = self.get(point(line, i));
+                if is_blocker(cell) {
+                    rest = i + 1;
+                } else if is_movable(cell) {
+                    let from = self.index(point(line, i));
+                    let to = self.index(point(line, rest));
+                    self.data.swap(from, to);
+                    rest += 1;
+                }
+            }
+        }
+    }
+
+    /// Push the cell at `from` one step in a direction, along with everything
+    /// in front of it that it shoves. `push` says what each cell does when
+    /// pushed, and objects spanning several cells pull the rest of themselves
+    /// along, which can push even more. If anything is blocked, or would be
+    /// pushed off the field, nothing moves and this returns false.
+    pub fn push_chain(
+        &mut self,
+        from: IPoint,
+        dir: Dirn,
+        push: impl Fn(IPoint, &T) -> Push,
+    ) -> bool {
+        let step = dir.as_point();
+        let mut moving = vec![from];
+        let mut seen = HashSet::from([from]);
+
+        let mut i = 0;
+        while i < moving.len() {
+            let next = moving[i] + step;
+            i += 1;
+            if seen.contains(&next) {
+                continue;
+            }
+            let Some(cell) = self.try_get(next) else {
+                return false;
+            };
+            match push(next, cell) {
+                Push::Empty => {}
+                Push::Blocked => return false,
+                Push::Moves(parts) => {
+                    for p in parts {
+                        if seen.insert(p) {
+                            moving.push(p);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Move the cells furthest ahead first, so each one swaps into space
+        // that's already empty.
+        moving.sort_by_key(|p| -(p.x * step.x + p.y * step.y));
+        for p in moving {
+            let (a, b) = (self.index(p), self.index(p + step));
+            self.data.swap(a, b);
+        }
+        true
+    }
+
+    fn index(&self, p: IPoint) -> usize {
+        (p.y * self.width + p.x) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DenseField, Dirn, Push, RIGHT};
+
+    fn field(lines: &[&str]) -> DenseField<u8> {
+        DenseField::from_lines(lines.iter().map(|l| l.to_string()).collect())
+    }
+
+    fn text(field: &DenseField<u8>) -> Vec<String> {
+        field
+            .rows()
+            .map(|r| String::from_utf8(r.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn tilt() {
+        let mut f = field(&["O.#.O", ".O..O", "O...."]);
+        let tilt = |f: &mut DenseField<u8>, dir| f.tilt(dir, |c| *c == b'O', |c| *c == b'#');
+
+        tilt(&mut f, Dirn::Up);
+        assert_eq!(text(&f), ["OO#.O", "O...O", "....."]);
+        tilt(&mut f, Dirn::Right);
+        assert_eq!(text(&f), ["OO#.O", "...OO", "....."]);
+        tilt(&mut f, Dirn::Down);
+        assert_eq!(text(&f), ["..#..", "....O", "OO.OO"]);
+        tilt(&mut f, Dirn::Left);
+        assert_eq!(text(&f), ["..#..", "O....", "OOOO."]);
+    }
+
+    #[test]
+    fn push_wide_boxes() {
+        // Wide boxes from 2024 day 15.
+        let mut f = field(&[
+            "##########",
+            "##......##",
+            "##..[]..##",
+            "##.[][].##",
+            "##..@...##",
+            "##########",
+        ]);
+        let push = |p, c: &u8| match c {
+            b'#' => Push::Blocked,
+            b'[' => Push::Moves(vec![p, p + RIGHT]),
+            b']' => Push::Moves(vec![p - RIGHT, p]),
+            b'@' => Push::Moves(vec![p]),
+            _ => Push::Empty,
+        };
+
+        let robot = f.find(&b'@').unwrap();
+        assert!(f.push_chain(robot, Dirn::Up, push));
+        assert_eq!(
+            text(&f),
+            [
+                "##########",
+                "##..[]..##",
+                "##.[]...##",
+                "##..@[].##",
+                "##......##",
+                "##########"
+            ]
+        );
+
+        // Now the top box is against the wall so nothing can move.
+        let robot = f.find(&b'@').unwrap();
+        assert!(!f.push_chain(robot, Dirn::Up, push));
+        assert_eq!(text(&f)[1], "##..[]..##");
+
+        assert!(f.push_chain(robot, Dirn::Left, push));
+        assert_eq!(text(&f)[3], "##.@.[].##");
+        let robot = robot - RIGHT;
+        assert!(f.push_chain(robot, Dirn::Left, push));
+        assert!(!f.push_chain(robot - RIGHT, Dirn::Left, push));
+        assert_eq!(text(&f)[3], "##@..[].##");
+    }
+}
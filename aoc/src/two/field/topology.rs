@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use crate::two::{DenseField, IPoint, pt};
+
+/// How the edges of a field connect, used to work out where a step from one
+/// cell to another actually lands.
+pub trait Topology {
+    /// Where stepping from `from` to `to` ends up in a field of this size, or
+    /// None if the step leaves the field. `from` is always inside the field,
+    /// and the result must be too. Anything outside is dropped as if it were
+    /// None.
+    fn resolve(&self, width: isize, height: isize, from: IPoint, to: IPoint) -> Option<IPoint>;
+}
+
+impl<T: Topology + ?Sized> Topology for &T {
+    fn resolve(&self, width: isize, height: isize, from: IPoint, to: IPoint) -> Option<IPoint> {
+        (**self).resolve(width, height, from, to)
+    }
+}
+
+fn inside(width: isize, height: isize, p: IPoint) -> bool {
+    (0..width).contains(&p.x) && (0..height).contains(&p.y)
+}
+
+/// Nothing outside the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounded;
+
+impl Topology for Bounded {
+    fn resolve(&self, width: isize, height: isize, _: IPoint, to: IPoint) -> Option<IPoint> {
+        inside(width, height, to).then_some(to)
+    }
+}
+
+/// Both axes wrap around, like the surface of a torus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Torus;
+
+impl Topology for Torus {
+    fn resolve(&self, width: isize, height: isize, _: IPoint, to: IPoint) -> Option<IPoint> {
+        Some(pt(to.x.rem_euclid(width), to.y.rem_euclid(height)))
+    }
+}
+
+/// Only one axis wraps around, the other is bounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cylinder {
+    /// Walking off the left comes back on the right.
+    WrapX,
+    /// Walking off the top comes back on the bottom.
+    WrapY,
+}
+
+impl Topology for Cylinder {
+    fn resolve(&self, width: isize, height: isize, _: IPoint, to: IPoint) -> Option<IPoint> {
+        let to = match self {
+            Cylinder::WrapX => pt(to.x.rem_euclid(width), to.y),
+            Cylinder::WrapY => pt(to.x, to.y.rem_euclid(height)),
+        };
+        inside(width, height, to).then_some(to)
+    }
+}
+
+/// A bounded field with extra links, where stepping onto a point instead
+/// lands somewhere else. The point stepped onto can be outside the field, for
+/// warps at the edges, or inside it, for portals like in a donut maze.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Portals {
+    links: HashMap<IPoint, IPoint>,
+}
+
+impl Portals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stepping onto `from` lands on `to`. Only goes one way.
+    pub fn link(&mut self, from: IPoint, to: IPoint) {
+        self.links.insert(from, to);
+    }
+
+    /// Link both ways, so stepping onto `a` lands on `b` and the other way
+    /// round.
+    pub fn link_both(&mut self, a: IPoint, b: IPoint) {
+        self.link(a, b);
+        self.link(b, a);
+    }
+}
+
+impl Topology for Portals {
+    fn resolve(&self, width: isize, height: isize, _: IPoint, to: IPoint) -> Option<IPoint> {
+        let to = self.links.get(&to).copied().unwrap_or(to);
+        inside(width, height, to).then_some(to)
+    }
+}
+
+/// Which offsets around a point count as its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stencil<'a> {
+    offsets: &'a [IPoint],
+}
+
+impl Stencil<'static> {
+    /// Up, left, right and down.
+    pub const FOUR: Self = Stencil {
+        offsets: &[pt(0, -1), pt(-1, 0), pt(1, 0), pt(0, 1)],
+    };
+
+    /// All eight surrounding cells, in row major order.
+    pub const EIGHT: Self = Stencil {
+        offsets: &[
+            pt(-1, -1),
+            pt(0, -1),
+            pt(1, -1),
+            pt(-1, 0),
+            pt(1, 0),
+            pt(-1, 1),
+            pt(0, 1),
+            pt(1, 1),
+        ],
+    };
+
+    /// Just the four diagonals.
+    pub const DIAGONALS: Self = Stencil {
+        offsets: &[pt(-1, -1), pt(1, -1), pt(-1, 1), pt(1, 1)],
+    };
+}
+
+impl<'a> Stencil<'a> {
+    /// Any other set of offsets, eg knight moves.
+    pub const fn new(offsets: &'a [IPoint]) -> Self {
+        Stencil { offsets }
+    }
+
+    pub fn offsets(&self) -> &'a [IPoint] {
+        self.offsets
+    }
+}
+
+impl<T> DenseField<T> {
+    /// Neighbours of a point, using the offsets in the stencil and the edges
+    /// given by the topology. Each value contains the neighbour value and the
+    /// point of that neighbour.
+    pub fn neighbours<'a>(
+        &'a self,
+        p: IPoint,
+        stencil: Stencil<'a>,
+        topology: impl Topology + 'a,
+    ) -> impl Iterator<Item = (&'a T, IPoint)> {
+        stencil.offsets.iter().filter_map(move |d| {
+            let n = topology.resolve(self.width, self.height, p, p + *d)?;
+            Some((self.try_get(n)?, n))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{
+        Bounded, Cylinder, DenseField, IPoint, Portals, Stencil, Topology, Torus, pt,
+    };
+
+    #[test]
+    fn topologies() {
+        let field = DenseField::new(4, 3, 0);
+        let n = |stencil, topology: &dyn Topology| {
+            let mut n: Vec<_> = field
+                .neighbours(pt(0, 0), stencil, topology)
+                .map(|n| n.1)
+                .collect();
+            n.sort_by_key(|p| (p.y, p.x));
+            n
+        };
+
+        assert_eq!(n(Stencil::FOUR, &Bounded), vec![pt(1, 0), pt(0, 1)]);
+        assert_eq!(n(Stencil::DIAGONALS, &Bounded), vec![pt(1, 1)]);
+        assert_eq!(
+            n(Stencil::FOUR, &Torus),
+            vec![pt(1, 0), pt(3, 0), pt(0, 1), pt(0, 2)]
+        );
+        assert_eq!(
+            n(Stencil::FOUR, &Cylinder::WrapX),
+            vec![pt(1, 0), pt(3, 0), pt(0, 1)]
+        );
+        assert_eq!(
+            n(Stencil::FOUR, &Cylinder::WrapY),
+            vec![pt(1, 0), pt(0, 1), pt(0, 2)]
+        );
+        assert_eq!(n(Stencil::EIGHT, &Torus).len(), 8);
+
+        let knight = [pt(1, 2), pt(2, 1), pt(-1, 2), pt(-2, 1)];
+        assert_eq!(n(Stencil::new(&knight), &Bounded), vec![pt(2, 1), pt(1, 2)]);
+
+        // Walking off the left edge at the top lands at the bottom right, and
+        // the cell to the right is a portal.
+        let mut portals = Portals::new();
+        portals.link(pt(-1, 0), pt(3, 2));
+        portals.link(pt(1, 0), pt(2, 1));
+        assert_eq!(
+            n(Stencil::FOUR, &portals),
+            vec![pt(0, 1), pt(2, 1), pt(3, 2)]
+        );
+    }
+
+    // Doesn't check the field at all.
+    struct Unbounded;
+
+    impl Topology for Unbounded {
+        fn resolve(&self, _: isize, _: isize, _: IPoint, to: IPoint) -> Option<IPoint> {
+            Some(to)
+        }
+    }
+
+    #[test]
+    fn outside_is_dropped() {
+        let field = DenseField::new(2, 2, 0);
+        let n = field.neighbours(pt(0, 0), Stencil::EIGHT, Unbounded);
+        assert_eq!(n.count(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::two::{DenseField, IPoint, PointsIter, pt};
+
+/// A borrowed rectangular window into a field. Points given to a `SubField`
+/// are relative to its own top left corner.
+#[derive(Debug, Clone, Copy)]
+pub struct SubField<'a, T> {
+    field: &'a DenseField<T>,
+    origin: IPoint,
+    width: isize,
+    height: isize,
+}
+
+impl<'a, T> SubField<'a, T> {
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Top left corner of the window in the underlying field.
+    pub fn origin(&self) -> IPoint {
+        self.origin
+    }
+
+    pub fn get(&self, p: IPoint) -> &'a T {
+        assert!(p.x >= 0 && p.x < self.width);
+        assert!(p.y >= 0 && p.y < self.height);
+        self.field.get(self.origin + p)
+    }
+
+    pub fn try_get(&self, p: IPoint) -> Option<&'a T> {
+        if (0..self.width).contains(&p.x) && (0..self.height).contains(&p.y) {
+            Some(self.field.get(self.origin + p))
+        } else {
+            None
+        }
+    }
+
+    /// Borrow a row of the window, which is a slice of the underlying row.
+    pub fn row(&self, y: isize) -> &'a [T] {
+        assert!(y >= 0 && y < self.height);
+        let x = self.origin.x as usize;
+        &self.field.row(self.origin.y + y)[x..x + self.width as usize]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// Points of the window in row major order, relative to the window.
+    pub fn points(&self) -> PointsIter {
+        PointsIter {
+            width: self.width,
+            height: self.height,
+            current: 0,
+        }
+    }
+
+    /// Copy the window out into its own field.
+    pub fn to_field(&self) -> DenseField<T>
+    where
+        T: Clone,
+    {
+        DenseField {
+            width: self.width,
+            height: self.height,
+            data: self.rows().flatten().cloned().collect(),
+        }
+    }
+}
+
+/// Windows, and building new fields out of parts of others.
+impl<T> DenseField<T> {
+    /// Borrow a rectangle of the field. The rectangle must be entirely inside
+    /// the field.
+    pub fn sub_field(&self, origin: IPoint, width: isize, height: isize) -> SubField<'_, T> {
+        assert!(width > 0 && height > 0);
+        assert!(origin.x >= 0 && origin.x + width <= self.width);
+        assert!(origin.y >= 0 && origin.y + height <= self.height);
+        SubField {
+            field: self,
+            origin,
+            width,
+            height,
+        }
+    }
+
+    /// Copy `src` into this field with its top left corner at `at`. Any part
+    /// of `src` that would land outside of this field is ignored.
+    pub fn blit(&mut self, src: &DenseField<T>, at: IPoint)
+    where
+        T: Clone,
+    {
+        self.blit_with(src, at, |dst, src| *dst = src.clone());
+    }
+
+    /// Like `blit` but combine each source cell into the destination cell with
+    /// `f`, for example to only copy some cells, or to count overlaps. The
+    /// source can have a different cell type.
+    pub fn blit_with<S>(&mut self, src: &DenseField<S>, at: IPoint, mut f: impl FnMut(&mut T, &S)) {
+        for p in src.points() {
+            if let Some(dst) = self.try_get_mut(at + p) {
+                f(dst, src.get(p));
+            }
+        }
+    }
+}
+
+impl<T: Clone> DenseField<T> {
+    /// Copy a rectangle of the field out into a new field.
+    pub fn crop(&self, origin: IPoint, width: isize, height: isize) -> DenseField<T> {
+        self.sub_field(origin, width, height).to_field()
+    }
+
+    /// Grow the field by `border` cells on every side, filled with `val`.
+    /// Existing points move by `(border, border)`.
+    pub fn pad(&self, border: isize, val: T) -> DenseField<T> {
+        assert!(border >= 0);
+        let mut padded = DenseField::new(self.width + 2 * border, self.height + 2 * border, val);
+        padded.blit(self, pt(border, border));
+        padded
+    }
+
+    /// Repeat the field `across` times horizontally and `down` times
+    /// vertically.
+    pub fn tile(&self, across: isize, down: isize) -> DenseField<T> {
+        assert!(across > 0 && down > 0);
+        let mut data = Vec::with_capacity(self.data.len() * (across * down) as usize);
+        for _ in 0..down {
+            for row in self.rows() {
+                for _ in 0..across {
+                    data.extend_from_slice(row);
+                }
+            }
+        }
+
+        DenseField {
+            width: self.width * across,
+            height: self.height * down,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DenseField, pt};
+
+    fn field(lines: &[&str]) -> DenseField<u8> {
+        DenseField::from_lines(lines.iter().map(|l| l.to_string()).collect())
+    }
+
+    #[test]
+    fn sub_field() {
+        let f = field(&["abcd", "efgh", "ijkl"]);
+        let sub = f.sub_field(pt(1, 1), 2, 2);
+        assert_eq!(*sub.get(pt(0, 0)), b'f');
+        assert_eq!(sub.try_get(pt(2, 0)), None);
+        assert_eq!(sub.rows().collect::<Vec<_>>(), vec![b"fg", b"jk"]);
+        assert_eq!(sub.to_field(), field(&["fg", "jk"]));
+        assert_eq!(f.crop(pt(2, 0), 2, 3), field(&["cd", "gh", "kl"]));
+    }
+
+    #[test]
+    fn blit() {
+        let mut f = field(&["....", "....", "...."]);
+        f.blit(&field(&["ab", "cd"]), pt(3, 1));
+        assert_eq!(f, field(&["....", "...a", "...c"]));
+
+        let mut f = field(&["....", "....", "...."]);
+        let shape = field(&["#.", "##"]);
+        f.blit_with(&shape, pt(-1, 0), |dst, src| {
+            if *src == b'#' {
+                *dst = b'#';
+            }
+        });
+        assert_eq!(f, field(&["....", "#...", "...."]));
+    }
+
+    #[test]
+    fn pad_and_tile() {
+        let f = field(&["ab", "cd"]);
+        assert_eq!(f.pad(1, b'.'), field(&["....", ".ab.", ".cd.", "...."]));
+        assert_eq!(
+            f.tile(3, 2),
+            field(&["ababab", "cdcdcd", "ababab", "cdcdcd"])
+        );
+    }
+}
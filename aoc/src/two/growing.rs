@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+
+use super::{DenseField, Dirn, IPoint, pt};
+
+/// A dense field that grows when written past its edges, for simulations that
+/// expand without bound like a pile of falling rocks. Only chosen directions
+/// are allowed to grow.
+///
+/// Points are logical, so growing up or left never changes the coordinates of
+/// existing cells. Anything outside the stored area reads as the fill value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GrowingField<T> {
+    // Rows are stored top to bottom. A deque lets us add and forget rows at
+    // either end cheaply.
+    data: VecDeque<T>,
+    width: isize,
+    height: isize,
+    // Logical point of the first stored cell.
+    origin: IPoint,
+    fill: T,
+    // Indexed by Dirn.
+    grow: [bool; 4],
+}
+
+impl<T: Clone> GrowingField<T> {
+    /// Create a field covering (0, 0) to (width - 1, height - 1), which can
+    /// grow in the given directions. It always has at least one cell.
+    pub fn new(width: isize, height: isize, fill: T, grow: &[Dirn]) -> Self {
+        assert!(width > 0 && height > 0);
+        let mut allowed = [false; 4];
+        for d in grow {
+            allowed[*d as usize] = true;
+        }
+
+        GrowingField {
+            data: VecDeque::from(vec![fill.clone(); (width * height) as usize]),
+            width,
+            height,
+            origin: pt(0, 0),
+            fill,
+            grow: allowed,
+        }
+    }
+
+    /// Start from an existing field, with the same coordinates.
+    pub fn from_field(field: &DenseField<T>, fill: T, grow: &[Dirn]) -> Self {
+        let mut growing = Self::new(field.width(), field.height(), fill, grow);
+        growing.data = field.data().iter().cloned().collect();
+        growing
+    }
+
+    /// Width of the stored area.
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    /// Height of the stored area.
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Inclusive top left and bottom right of the stored area. Grows as
+    /// cells are written, and shrinks as rows are forgotten.
+    pub fn bounds(&self) -> (IPoint, IPoint) {
+        (
+            self.origin,
+            self.origin + pt(self.width - 1, self.height - 1),
+        )
+    }
+
+    fn index(&self, p: IPoint) -> Option<usize> {
+        let local = p - self.origin;
+        if (0..self.width).contains(&local.x) && (0..self.height).contains(&local.y) {
+            Some((local.y * self.width + local.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Get a cell, or the fill value if it's outside of the stored area.
+    pub fn get(&self, p: IPoint) -> &T {
+        self.try_get(p).unwrap_or(&self.fill)
+    }
+
+    /// Get a cell only if it's inside the stored area.
+    pub fn try_get(&self, p: IPoint) -> Option<&T> {
+        self.index(p).map(|i| &self.data[i])
+    }
+
+    /// Get a cell to write, growing the field to include it if needed. Panics
+    /// if that means growing in a direction that isn't allowed.
+    pub fn get_mut(&mut self, p: IPoint) -> &mut T {
+        self.grow_to(p);
+        let i = self.index(p).unwrap();
+        &mut self.data[i]
+    }
+
+    /// Iterate over a row of the stored area, left to right.
+    pub fn row(&self, y: isize) -> impl Iterator<Item = &T> {
+        let local = y - self.origin.y;
+        assert!((0..self.height).contains(&local));
+        let start = (local * self.width) as usize;
+        self.data.range(start..start + self.width as usize)
+    }
+
+    /// Forget every row above `y`, so that memory stays bounded as we grow
+    /// down. Forgotten rows read as the fill value. Writing to one again
+    /// needs the field to be able to grow up, otherwise it panics like any
+    /// other write past the edge. Panics if that would forget every row.
+    pub fn forget_rows_before(&mut self, y: isize) {
+        let n = (y - self.origin.y).max(0);
+        assert!(n < self.height, "forgetting rows before {y} leaves nothing");
+        self.data.drain(..(n * self.width) as usize);
+        self.height -= n;
+        self.origin.y += n;
+    }
+
+    /// Forget every row below `y`, so that memory stays bounded as we grow
+    /// up. Like `forget_rows_before`, writing to them again needs the field to
+    /// be able to grow down, and it panics if that would forget every row.
+    pub fn forget_rows_after(&mut self, y: isize) {
+        let keep = (y - self.origin.y + 1).min(self.height);
+        assert!(keep > 0, "forgetting rows after {y} leaves nothing");
+        self.data.truncate((keep * self.width) as usize);
+        self.height = keep;
+    }
+
+    /// Copy the stored area into a dense field. Also returns the logical point
+    /// of the dense field's origin.
+    pub fn to_field(&self) -> (DenseField<T>, IPoint) {
+        let mut field = DenseField::new(self.width, self.height, self.fill.clone());
+        for (p, v) in field.points().zip(&self.data) {
+            *field.get_mut(p) = v.clone();
+        }
+        (field, self.origin)
+    }
+
+    fn grow_to(&mut self, p: IPoint) {
+        let (min, max) = self.bounds();
+
+        let check = |allowed: bool, dirn: Dirn| {
+            assert!(
+                allowed,
+                "{p:?} is outside {min:?}..={max:?} and can't grow {dirn:?}"
+            );
+        };
+
+        if p.y < min.y {
+            check(self.grow[Dirn::Up as usize], Dirn::Up);
+            let rows = min.y - p.y;
+            for _ in 0..rows * self.width {
+                self.data.push_front(self.fill.clone());
+            }
+            self.height += rows;
+            self.origin.y = p.y;
+        } else if p.y > max.y {
+            check(self.grow[Dirn::Down as usize], Dirn::Down);
+            let rows = p.y - max.y;
+            self.data.resize(
+                ((self.height + rows) * self.width) as usize,
+                self.fill.clone(),
+            );
+            self.height += rows;
+        }
+
+        if p.x < min.x || p.x > max.x {
+            let (left, right) = if p.x < min.x {
+                check(self.grow[Dirn::Left as usize], Dirn::Left);
+                // Grow by at least the current width so that repeatedly
+                // growing sideways is amortised.
+                ((min.x - p.x).max(self.width), 0)
+            } else {
+                check(self.grow[Dirn::Right as usize], Dirn::Right);
+                (0, (p.x - max.x).max(self.width))
+            };
+            self.widen(left, right);
+        }
+    }
+
+    fn widen(&mut self, left: isize, right: isize) {
+        let new_width = self.width + left + right;
+        let mut data = VecDeque::with_capacity((new_width * self.height) as usize);
+        for y in 0..self.height {
+            data.extend(std::iter::repeat_n(self.fill.clone(), left as usize));
+            let start = (y * self.width) as usize;
+            data.extend(self.data.range(start..start + self.width as usize).cloned());
+            data.extend(std::iter::repeat_n(self.fill.clone(), right as usize));
+        }
+        self.data = data;
+        self.width = new_width;
+        self.origin.x -= left;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DenseField, Dirn, GrowingField, pt};
+
+    #[test]
+    fn grows_up() {
+        let mut field = GrowingField::new(7, 1, '.', &[Dirn::Up]);
+        *field.get_mut(pt(0, 0)) = '#';
+        *field.get_mut(pt(3, -10)) = '#';
+
+        assert_eq!(field.bounds(), (pt(0, -10), pt(6, 0)));
+        assert_eq!(*field.get(pt(0, 0)), '#');
+        assert_eq!(*field.get(pt(3, -10)), '#');
+        assert_eq!(*field.get(pt(3, -9)), '.');
+        assert_eq!(*field.get(pt(100, 100)), '.');
+
+        // Drop everything but the top few rows.
+        field.forget_rows_after(-8);
+        assert_eq!(field.bounds(), (pt(0, -10), pt(6, -8)));
+        assert_eq!(field.try_get(pt(0, 0)), None);
+        assert_eq!(*field.get(pt(3, -10)), '#');
+        assert_eq!(field.row(-10).collect::<String>(), "...#...");
+    }
+
+    #[test]
+    fn grows_sideways_and_down() {
+        let start = DenseField::<u8>::from_lines(vec!["ab".to_string(), "cd".to_string()]);
+        let mut field = GrowingField::from_field(&start, b'.', &[Dirn::Left, Dirn::Down]);
+
+        *field.get_mut(pt(-1, 3)) = b'x';
+        assert_eq!(*field.get(pt(1, 1)), b'd');
+        assert_eq!(*field.get(pt(-1, 3)), b'x');
+
+        let (dense, origin) = field.to_field();
+        assert_eq!(origin, field.bounds().0);
+        assert_eq!(*dense.get(pt(1, 1) - origin), b'd');
+
+        field.forget_rows_before(3);
+        assert_eq!(field.height(), 1);
+        assert_eq!(*field.get(pt(0, 0)), b'.');
+        assert_eq!(*field.get(pt(-1, 3)), b'x');
+    }
+
+    #[test]
+    #[should_panic]
+    fn forget_everything() {
+        let mut field = GrowingField::new(3, 3, 0, &[Dirn::Down]);
+        field.forget_rows_before(3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_forgotten_row() {
+        let mut field = GrowingField::new(3, 3, 0, &[Dirn::Down]);
+        field.forget_rows_before(2);
+        *field.get_mut(pt(0, 0)) = 1;
+    }
+
+    #[test]
+    #[should_panic]
+    fn disallowed_growth() {
+        let mut field = GrowingField::new(3, 3, 0, &[Dirn::Up]);
+        *field.get_mut(pt(0, 3)) = 1;
+    }
+}
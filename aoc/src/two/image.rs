@@ -0,0 +1,278 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::two::{DenseField, IPoint, SparseField, pt};
+
+/// Red, green and blue.
+pub type Rgb = [u8; 3];
+
+/// A picture of a field, to save and look at in an image viewer when it's
+/// too big or too busy for the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// One pixel per cell, coloured by the palette.
+    pub fn from_field<T>(field: &DenseField<T>, palette: impl Fn(&T) -> Rgb) -> Self {
+        Image {
+            width: field.width() as usize,
+            height: field.height() as usize,
+            pixels: field.points().map(|p| palette(field.get(p))).collect(),
+        }
+    }
+
+    /// Covers just the bounds of the cells in the field, with everything
+    /// missing drawn as `empty`.
+    pub fn from_sparse<T>(field: &SparseField<T>, empty: Rgb, palette: impl Fn(&T) -> Rgb) -> Self {
+        let (min, max) = field.bounds().expect("nothing to draw");
+        Self::from_fn(min, max, |p| field.get(p).map_or(empty, &palette))
+    }
+
+    /// White points on black, between two corners inclusive. Points outside
+    /// are left out.
+    pub fn from_points(points: impl IntoIterator<Item = IPoint>, min: IPoint, max: IPoint) -> Self {
+        let mut image = Self::from_fn(min, max, |_| [0; 3]);
+        for p in points {
+            let (x, y) = (p.x - min.x, p.y - min.y);
+            if (0..image.width as isize).contains(&x) && (0..image.height as isize).contains(&y) {
+                image.pixels[y as usize * image.width + x as usize] = [255; 3];
+            }
+        }
+        image
+    }
+
+    fn from_fn(min: IPoint, max: IPoint, colour: impl Fn(IPoint) -> Rgb) -> Self {
+        assert!(min.x <= max.x && min.y <= max.y);
+        Image {
+            width: (max.x - min.x + 1) as usize,
+            height: (max.y - min.y + 1) as usize,
+            pixels: (min.y..=max.y)
+                .flat_map(|y| (min.x..=max.x).map(move |x| pt(x, y)))
+                .map(colour)
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Blow each pixel up into a square, as one pixel per cell is tiny.
+    pub fn scaled(&self, scale: usize) -> Self {
+        assert!(scale > 0);
+        let width = self.width * scale;
+        Image {
+            width,
+            height: self.height * scale,
+            pixels: (0..self.height * scale)
+                .flat_map(|y| (0..width).map(move |x| self.pixel(x / scale, y / scale)))
+                .collect(),
+        }
+    }
+
+    /// Binary PPM, which most viewers can open and needs no libraries.
+    pub fn write_ppm(&self, mut w: impl Write) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(self.pixels.as_flattened())
+    }
+
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, w: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(self.pixels.as_flattened())
+            .map_err(io::Error::other)?;
+        // Writes the end chunk, which dropping would do without any errors.
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Save as PNG if the path ends in `.png`, which needs the `png`
+    /// feature, otherwise as PPM. Without the feature a `.png` path is an
+    /// error and nothing is written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let png = path.extension().is_some_and(|e| e == "png");
+        if png && cfg!(not(feature = "png")) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("saving {} needs the png feature", path.display()),
+            ));
+        }
+
+        let mut w = BufWriter::new(File::create(path)?);
+        #[cfg(feature = "png")]
+        if png {
+            self.write_png(&mut w)?;
+            return w.flush();
+        }
+        self.write_ppm(&mut w)?;
+        w.flush()
+    }
+}
+
+impl<T> DenseField<T> {
+    /// Save a picture of the field, see `Image::save`. Each cell is a square
+    /// of `scale` pixels.
+    pub fn write_image(
+        &self,
+        path: impl AsRef<Path>,
+        scale: usize,
+        palette: impl Fn(&T) -> Rgb,
+    ) -> io::Result<()> {
+        Image::from_field(self, palette).scaled(scale).save(path)
+    }
+}
+
+impl<T> SparseField<T> {
+    /// Save a picture of the field's bounds, see `Image::save`.
+    pub fn write_image(
+        &self,
+        path: impl AsRef<Path>,
+        scale: usize,
+        empty: Rgb,
+        palette: impl Fn(&T) -> Rgb,
+    ) -> io::Result<()> {
+        Image::from_sparse(self, empty, palette)
+            .scaled(scale)
+            .save(path)
+    }
+}
+
+/// Numbered images in a directory, one per step of a simulation, to flip
+/// through in an image viewer.
+#[derive(Debug)]
+pub struct Frames {
+    dir: PathBuf,
+    extension: &'static str,
+    scale: usize,
+    next: usize,
+}
+
+impl Frames {
+    /// Frames go in `dir`, which is created if needed, as `ppm` or `png`.
+    pub fn new(dir: impl Into<PathBuf>, extension: &'static str, scale: usize) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Frames {
+            dir,
+            extension,
+            scale,
+            next: 0,
+        })
+    }
+
+    /// Save the next frame, returning where it went.
+    pub fn save(&mut self, image: &Image) -> io::Result<PathBuf> {
+        let path = self
+            .dir
+            .join(format!("{:05}.{}", self.next, self.extension));
+        image.scaled(self.scale).save(&path)?;
+        self.next += 1;
+        Ok(path)
+    }
+
+    /// Number of frames saved so far.
+    pub fn len(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::two::{DenseField, Frames, Image, SparseField, pt};
+
+    #[test]
+    fn ppm() {
+        let mut field = DenseField::new(3, 2, false);
+        *field.get_mut(pt(1, 0)) = true;
+        let image = Image::from_field(&field, |c| if *c { [255, 0, 0] } else { [0; 3] });
+
+        let mut out = vec![];
+        image.write_ppm(&mut out).unwrap();
+        let header = b"P6\n3 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 3 * 2 * 3);
+        assert_eq!(&out[header.len()..header.len() + 6], &[0, 0, 0, 255, 0, 0]);
+
+        let big = image.scaled(4);
+        assert_eq!((big.width(), big.height()), (12, 8));
+        assert_eq!(big.pixel(4, 3), [255, 0, 0]);
+        assert_eq!(big.pixel(8, 3), [0; 3]);
+    }
+
+    #[test]
+    fn sparse_and_points() {
+        let field = SparseField::from_iter([(pt(-2, 5), 'a'), (pt(1, 6), 'b')]);
+        let image = Image::from_sparse(&field, [1; 3], |c| [*c as u8; 3]);
+        assert_eq!((image.width(), image.height()), (4, 2));
+        assert_eq!(image.pixel(0, 0), [b'a'; 3]);
+        assert_eq!(image.pixel(3, 1), [b'b'; 3]);
+        assert_eq!(image.pixel(1, 0), [1; 3]);
+
+        let image = Image::from_points([pt(0, 0), pt(2, 1), pt(9, 9)], pt(0, 0), pt(2, 1));
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.pixel(2, 1), [255; 3]);
+        assert_eq!(image.pixel(1, 1), [0; 3]);
+    }
+
+    #[test]
+    fn frames() {
+        let dir = env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+        let mut frames = Frames::new(&dir, "ppm", 2).unwrap();
+        let field = DenseField::new(2, 2, 7u8);
+        for _ in 0..3 {
+            frames
+                .save(&Image::from_field(&field, |c| [*c; 3]))
+                .unwrap();
+        }
+        assert_eq!(frames.len(), 3);
+
+        let last = fs::read(dir.join("00002.ppm")).unwrap();
+        assert!(last.starts_with(b"P6\n4 4\n255\n"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png() {
+        let image = Image::from_points([pt(1, 1)], pt(0, 0), pt(2, 2));
+        let mut out = vec![];
+        image.write_png(&mut out).unwrap();
+        assert!(out.starts_with(b"\x89PNG"));
+        assert_eq!(&out[out.len() - 8..out.len() - 4], b"IEND");
+    }
+
+    #[cfg(not(feature = "png"))]
+    #[test]
+    fn png_needs_feature() {
+        let path = env::temp_dir().join(format!("aoc-no-png-{}.png", std::process::id()));
+        let image = Image::from_points([], pt(0, 0), pt(1, 1));
+        let err = image.save(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        assert!(!path.exists());
+    }
+}
@@ -3,3 +3,24 @@ pub use point::*;
 
 mod field;
 pub use field::*;
+
+mod sparse;
+pub use sparse::*;
+
+mod growing;
+pub use growing::*;
+
+mod cube;
+pub use cube::*;
+
+mod automaton;
+pub use automaton::*;
+
+mod periodic;
+pub use periodic::*;
+
+mod image;
+pub use image::*;
+
+// Derive macro shares its name with the `GridCell` trait, like serde does.
+pub use aoc_derive::GridCell;
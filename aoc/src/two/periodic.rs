@@ -0,0 +1,164 @@
+use super::{IPoint, pt};
+
+/// Which cells of a field are blocked at each moment, for obstacles that
+/// move in a pattern that repeats, like blizzards blowing around a valley.
+/// Every moment of one period is worked out up front and stored one bit per
+/// cell, so checking a cell at any time is just a lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PeriodicField {
+    width: isize,
+    height: isize,
+    period: usize,
+    // Each layer is one moment, rounded up to whole words.
+    words_per_layer: usize,
+    bits: Vec<u64>,
+}
+
+impl PeriodicField {
+    /// Work out every moment of the period with a function saying whether a
+    /// cell is blocked at a time from 0 to period - 1.
+    pub fn from_fn(
+        width: isize,
+        height: isize,
+        period: usize,
+        mut blocked: impl FnMut(IPoint, usize) -> bool,
+    ) -> Self {
+        let mut field = Self::empty(width, height, period);
+        for t in 0..period {
+            for y in 0..height {
+                for x in 0..width {
+                    if blocked(pt(x, y), t) {
+                        field.set(pt(x, y), t);
+                    }
+                }
+            }
+        }
+        field
+    }
+
+    /// Obstacles that each start at a point and move by a fixed velocity
+    /// every step, wrapping around the edges. The pattern repeats after
+    /// lcm(width, height) steps, which is the period.
+    pub fn from_movers(
+        width: isize,
+        height: isize,
+        movers: impl IntoIterator<Item = (IPoint, IPoint)>,
+    ) -> Self {
+        let movers: Vec<_> = movers.into_iter().collect();
+        let period = lcm(width as usize, height as usize);
+        let mut field = Self::empty(width, height, period);
+        for t in 0..period {
+            for (start, velocity) in &movers {
+                let p = *start + *velocity * t as isize;
+                field.set(pt(p.x.rem_euclid(width), p.y.rem_euclid(height)), t);
+            }
+        }
+        field
+    }
+
+    fn empty(width: isize, height: isize, period: usize) -> Self {
+        assert!(width > 0 && height > 0 && period > 0);
+        let words_per_layer = ((width * height) as usize).div_ceil(64);
+        PeriodicField {
+            width,
+            height,
+            period,
+            words_per_layer,
+            bits: vec![0; words_per_layer * period],
+        }
+    }
+
+    fn bit(&self, p: IPoint, t: usize) -> (usize, u64) {
+        let i = (p.y * self.width + p.x) as usize;
+        let word = (t % self.period) * self.words_per_layer + i / 64;
+        (word, 1 << (i % 64))
+    }
+
+    fn set(&mut self, p: IPoint, t: usize) {
+        let (word, mask) = self.bit(p, t);
+        self.bits[word] |= mask;
+    }
+
+    pub fn width(&self) -> isize {
+        self.width
+    }
+
+    pub fn height(&self) -> isize {
+        self.height
+    }
+
+    /// Number of steps before the obstacles are back where they started.
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Whether a point is blocked at a time, which can be any time since it
+    /// wraps around the period. Points outside the field aren't blocked.
+    pub fn is_blocked(&self, p: IPoint, t: usize) -> bool {
+        if !(0..self.width).contains(&p.x) || !(0..self.height).contains(&p.y) {
+            return false;
+        }
+        let (word, mask) = self.bit(p, t);
+        self.bits[word] & mask != 0
+    }
+
+    /// Whether a point is inside the field and clear at a time.
+    pub fn is_free(&self, p: IPoint, t: usize) -> bool {
+        (0..self.width).contains(&p.x) && (0..self.height).contains(&p.y) && !self.is_blocked(p, t)
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod test {
+    use crate::two::{DOWN, LEFT, PeriodicField, RIGHT, UP, pt};
+
+    #[test]
+    fn movers() {
+        // Blizzards from the 2022 day 24 example.
+        let field = PeriodicField::from_movers(
+            6,
+            4,
+            [
+                (pt(0, 0), RIGHT),
+                (pt(1, 0), RIGHT),
+                (pt(3, 0), LEFT),
+                (pt(3, 1), UP),
+                (pt(5, 3), DOWN),
+            ],
+        );
+        assert_eq!(field.period(), 12);
+
+        assert!(field.is_blocked(pt(0, 0), 0));
+        assert!(!field.is_blocked(pt(0, 0), 1));
+        assert!(field.is_blocked(pt(2, 0), 1));
+        assert!(field.is_blocked(pt(2, 0), 2));
+        assert!(!field.is_blocked(pt(5, 0), 2));
+        assert!(field.is_blocked(pt(5, 0), 1));
+        assert!(field.is_blocked(pt(3, 0), 1));
+        assert!(field.is_blocked(pt(5, 0), 4));
+        assert!(field.is_blocked(pt(5, 0), 4 + 12 * 1000));
+
+        assert!(!field.is_blocked(pt(-1, 0), 0));
+        assert!(!field.is_free(pt(-1, 0), 0));
+        assert!(field.is_free(pt(4, 2), 0));
+    }
+
+    #[test]
+    fn from_fn() {
+        // A wall that sweeps down a big field, which spans a few words.
+        let field = PeriodicField::from_fn(100, 5, 5, |p, t| p.y == t as isize);
+        for t in 0..10 {
+            for y in 0..5 {
+                assert_eq!(field.is_blocked(pt(73, y), t), y == (t % 5) as isize);
+            }
+        }
+    }
+}
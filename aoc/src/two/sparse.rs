@@ -0,0 +1,322 @@
+use std::{cell::Cell, collections::HashMap, fmt::Display};
+
+use super::{DenseField, IPoint, Point, Stencil, pt};
+
+/// An unbounded 2D field backed by a hash map, for when a grid grows or the
+/// interesting cells are spread out. Missing points are simply empty. Keeps
+/// track of the bounding box of its cells.
+#[derive(Debug, Clone)]
+pub struct SparseField<T> {
+    cells: HashMap<IPoint, T>,
+    // Bounds only ever need to grow on insert, but a remove might shrink them.
+    // Rather than scan on every remove we mark them stale and recalculate the
+    // next time they're asked for.
+    bounds: Cell<Option<(IPoint, IPoint)>>,
+    stale: Cell<bool>,
+}
+
+impl<T> Default for SparseField<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SparseField<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl<T: Eq> Eq for SparseField<T> {}
+
+impl<T> SparseField<T> {
+    pub fn new() -> Self {
+        SparseField {
+            cells: HashMap::new(),
+            bounds: Cell::new(None),
+            stale: Cell::new(false),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, p: IPoint) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: IPoint) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    pub fn contains(&self, p: IPoint) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    /// Set a cell, returning the previous value if there was one.
+    pub fn insert(&mut self, p: IPoint, val: T) -> Option<T> {
+        if !self.stale.get() {
+            let grown = match self.bounds.get() {
+                None => (p, p),
+                Some((min, max)) => (
+                    pt(min.x.min(p.x), min.y.min(p.y)),
+                    pt(max.x.max(p.x), max.y.max(p.y)),
+                ),
+            };
+            self.bounds.set(Some(grown));
+        }
+        self.cells.insert(p, val)
+    }
+
+    pub fn remove(&mut self, p: IPoint) -> Option<T> {
+        let old = self.cells.remove(&p);
+        if let (Some(_), Some((min, max))) = (&old, self.bounds.get()) {
+            // Only removing a cell on the edge can shrink the bounds.
+            if p.x == min.x || p.x == max.x || p.y == min.y || p.y == max.y {
+                self.stale.set(true);
+            }
+        }
+        old
+    }
+
+    /// The smallest rectangle containing every cell, as the inclusive top left
+    /// and bottom right corners. None if the field is empty.
+    pub fn bounds(&self) -> Option<(IPoint, IPoint)> {
+        if self.stale.get() {
+            let bounds = self.cells.keys().fold(None, |acc, &p| match acc {
+                None => Some((p, p)),
+                Some((min, max)) => Some((
+                    pt(min.x.min(p.x), min.y.min(p.y)),
+                    pt(max.x.max(p.x), max.y.max(p.y)),
+                )),
+            });
+            self.bounds.set(bounds);
+            self.stale.set(false);
+        }
+        self.bounds.get()
+    }
+
+    /// Width and height of the bounding box, zero if empty.
+    pub fn size(&self) -> (isize, isize) {
+        match self.bounds() {
+            Some((min, max)) => (max.x - min.x + 1, max.y - min.y + 1),
+            None => (0, 0),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IPoint, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = IPoint> + '_ {
+        self.cells.keys().copied()
+    }
+
+    /// Remove every cell, keeping the allocated memory to reuse.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.set(None);
+        self.stale.set(false);
+    }
+
+    /// Wrap a point into the bounding box, as if the field was the surface of
+    /// a torus.
+    fn wrap(&self, p: IPoint) -> IPoint {
+        let Some((min, _)) = self.bounds() else {
+            return p;
+        };
+        let (w, h) = self.size();
+        pt(
+            min.x + (p.x - min.x).rem_euclid(w),
+            min.y + (p.y - min.y).rem_euclid(h),
+        )
+    }
+
+    fn lookup(&self, ps: impl IntoIterator<Item = IPoint>) -> impl Iterator<Item = (&T, IPoint)> {
+        ps.into_iter()
+            .filter_map(|p| self.cells.get(&p).map(|v| (v, p)))
+    }
+
+    /// The eight possible neighbours around this point, only returning those
+    /// that are set.
+    pub fn neighbours8_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(Stencil::EIGHT.offsets().iter().map(move |d| p + *d))
+    }
+
+    /// Neighbours as if the bounding box was the surface of a torus. Only set
+    /// neighbours are returned.
+    pub fn neighbours8_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(
+            Stencil::EIGHT
+                .offsets()
+                .iter()
+                .map(move |d| self.wrap(p + *d)),
+        )
+    }
+
+    /// Up down left right neighbours that are set.
+    pub fn neighbours4_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(Stencil::FOUR.offsets().iter().map(move |d| p + *d))
+    }
+
+    /// Up down left right neighbours that are set, like a torus.
+    pub fn neighbours4_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(
+            Stencil::FOUR
+                .offsets()
+                .iter()
+                .map(move |d| self.wrap(p + *d)),
+        )
+    }
+
+    // Direct diagonals from this point that are set.
+    pub fn diagonals_bounded(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(Stencil::DIAGONALS.offsets().iter().map(move |d| p + *d))
+    }
+
+    /// Direct diagonals that are set, like a torus.
+    pub fn diagonals_torus(&self, p: IPoint) -> impl Iterator<Item = (&T, Point<isize>)> {
+        self.lookup(
+            Stencil::DIAGONALS
+                .offsets()
+                .iter()
+                .map(move |d| self.wrap(p + *d)),
+        )
+    }
+
+    /// Take the cells of a dense field that `keep` returns true for.
+    pub fn from_dense(field: &DenseField<T>, keep: impl Fn(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        field
+            .points()
+            .filter(|p| keep(field.get(*p)))
+            .map(|p| (p, field.get(p).clone()))
+            .collect()
+    }
+
+    /// Create a dense field covering the bounding box, with missing cells set
+    /// to `empty`. Also returns the top left corner of the bounding box, which
+    /// is where the dense field's origin is. Panics if empty.
+    pub fn to_dense(&self, empty: T) -> (DenseField<T>, IPoint)
+    where
+        T: Clone,
+    {
+        let (min, _) = self.bounds().expect("empty sparse field");
+        let (w, h) = self.size();
+        let mut field = DenseField::new(w, h, empty);
+        for (p, v) in self.iter() {
+            *field.get_mut(p - min) = v.clone();
+        }
+        (field, min)
+    }
+}
+
+impl<T: Display> SparseField<T> {
+    /// Print the bounding box, using `empty` for missing cells.
+    pub fn debug_print(&self, empty: char) {
+        let Some((min, max)) = self.bounds() else {
+            return;
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.get(pt(x, y)) {
+                    Some(v) => print!("{v}"),
+                    None => print!("{empty}"),
+                }
+            }
+            println!()
+        }
+    }
+}
+
+impl<T> FromIterator<(IPoint, T)> for SparseField<T> {
+    fn from_iter<I: IntoIterator<Item = (IPoint, T)>>(iter: I) -> Self {
+        let mut field = SparseField::new();
+        for (p, v) in iter {
+            field.insert(p, v);
+        }
+        field
+    }
+}
+
+impl<T> Extend<(IPoint, T)> for SparseField<T> {
+    fn extend<I: IntoIterator<Item = (IPoint, T)>>(&mut self, iter: I) {
+        for (p, v) in iter {
+            self.insert(p, v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::two::{DenseField, SparseField, pt};
+
+    #[test]
+    fn bounds() {
+        let mut field = SparseField::new();
+        assert_eq!(field.bounds(), None);
+
+        field.insert(pt(2, 3), 'a');
+        field.insert(pt(-1, 5), 'b');
+        field.insert(pt(0, 0), 'c');
+        assert_eq!(field.bounds(), Some((pt(-1, 0), pt(2, 5))));
+        assert_eq!(field.size(), (4, 6));
+
+        field.remove(pt(-1, 5));
+        assert_eq!(field.bounds(), Some((pt(0, 0), pt(2, 3))));
+
+        // Inserting while stale shouldn't lose the shrink.
+        field.remove(pt(2, 3));
+        field.insert(pt(1, 1), 'd');
+        assert_eq!(field.bounds(), Some((pt(0, 0), pt(1, 1))));
+
+        field.remove(pt(0, 0));
+        field.remove(pt(1, 1));
+        assert_eq!(field.bounds(), None);
+        assert!(field.is_empty());
+    }
+
+    #[test]
+    fn neighbours() {
+        let field: SparseField<()> = [pt(0, 0), pt(1, 1), pt(2, 0), pt(5, 5)]
+            .into_iter()
+            .map(|p| (p, ()))
+            .collect();
+
+        let n8: HashSet<_> = field.neighbours8_bounded(pt(1, 0)).map(|n| n.1).collect();
+        assert_eq!(n8, HashSet::from([pt(0, 0), pt(1, 1), pt(2, 0)]));
+
+        let n4: HashSet<_> = field.neighbours4_bounded(pt(1, 0)).map(|n| n.1).collect();
+        assert_eq!(n4, HashSet::from([pt(0, 0), pt(1, 1), pt(2, 0)]));
+
+        let diag: HashSet<_> = field.diagonals_bounded(pt(1, 0)).map(|n| n.1).collect();
+        assert!(diag.is_empty());
+
+        // Bounds are (0,0) to (5,5), so left of (0,0) wraps to (5,0) which
+        // isn't set, but up-left wraps to (5,5) which is.
+        let torus: HashSet<_> = field.neighbours8_torus(pt(0, 0)).map(|n| n.1).collect();
+        assert_eq!(torus, HashSet::from([pt(1, 1), pt(5, 5)]));
+    }
+
+    #[test]
+    fn dense_round_trip() {
+        let dense = DenseField::<u8>::from_lines(vec!["#..".to_string(), ".#.".to_string()]);
+        let sparse = SparseField::from_dense(&dense, |c| *c == b'#');
+        assert_eq!(sparse.len(), 2);
+
+        let mut moved = SparseField::new();
+        moved.extend(sparse.iter().map(|(p, v)| (p + pt(10, -3), *v)));
+        let (back, origin) = moved.to_dense(b'.');
+        assert_eq!(origin, pt(10, -3));
+        assert_eq!(back, dense.crop(pt(0, 0), 2, 2));
+    }
+}
@@ -1,81 +1,17 @@
-use core::panic;
 use std::{
     cmp::Ordering,
     fs::File,
     io::{BufRead, BufReader},
 };
 
-use serde::Deserialize;
+use aoc::Nested;
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
-#[serde(untagged)]
-enum Data {
-    Int(i64),
-    List(Vec<Data>),
-}
+type Data = Nested<i64>;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 struct Pair {
-    left: Vec<Data>,
-    right: Vec<Data>,
-}
-
-fn is_in_order(left: &[Data], right: &[Data]) -> Ordering {
-    let max_len = left.len().max(right.len());
-
-    if max_len == 0 {
-        return Ordering::Equal;
-    }
-
-    for i in 0..max_len {
-        let left = left.get(i);
-        let right = right.get(i);
-
-        match (left, right) {
-            (None, None) => panic!("how to order?"),
-            (None, Some(_)) => {
-                // left side ran out, so we're in order...
-                return Ordering::Less;
-            }
-            (Some(_), None) => {
-                // right side ran out, not in right order
-                return Ordering::Greater;
-            }
-            (Some(Data::Int(left)), Some(Data::Int(right))) => {
-                if left == right {
-                    continue;
-                }
-                return if left < right {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                };
-            }
-            (Some(Data::Int(left)), Some(Data::List(right))) => {
-                let left = vec![Data::Int(*left)];
-                match is_in_order(&left, right) {
-                    Ordering::Less => return Ordering::Less,
-                    Ordering::Greater => return Ordering::Greater,
-                    _ => {}
-                }
-            }
-            (Some(Data::List(left)), Some(Data::Int(right))) => {
-                let right = vec![Data::Int(*right)];
-                match is_in_order(left, &right) {
-                    Ordering::Less => return Ordering::Less,
-                    Ordering::Greater => return Ordering::Greater,
-                    _ => {}
-                }
-            }
-            (Some(Data::List(left)), Some(Data::List(right))) => match is_in_order(left, right) {
-                Ordering::Less => return Ordering::Less,
-                Ordering::Greater => return Ordering::Greater,
-                _ => {}
-            },
-        }
-    }
-
-    Ordering::Equal
+    left: Data,
+    right: Data,
 }
 
 fn main() {
@@ -91,22 +27,22 @@ fn main() {
     let part1 = pairs
         .iter()
         .enumerate()
-        .filter(|(_, pair)| is_in_order(&pair.left, &pair.right) == Ordering::Less)
+        .filter(|(_, pair)| pair.left.cmp_promoting(&pair.right) == Ordering::Less)
         .map(|(i, _)| i + 1)
         .sum::<usize>();
 
-    let mut packets: Vec<Vec<Data>> = pairs
+    let mut packets: Vec<Data> = pairs
         .into_iter()
         .flat_map(|pair| [pair.left, pair.right])
         .collect();
 
     // Divider packets.
-    let div1 = vec![Data::List(vec![Data::Int(2)])];
-    let div2 = vec![Data::List(vec![Data::Int(6)])];
+    let div1 = Data::parse("[[2]]").unwrap();
+    let div2 = Data::parse("[[6]]").unwrap();
     packets.push(div1.clone());
     packets.push(div2.clone());
 
-    packets.sort_by(|a, b| is_in_order(a, b));
+    packets.sort_by(Data::cmp_promoting);
 
     println!("{:#?}", packets);
 
@@ -133,8 +69,8 @@ fn parse(r: impl BufRead) -> Vec<Pair> {
     lines
         .chunks_exact(2)
         .map(|pair| {
-            let left = serde_json::from_str(&pair[0]).unwrap();
-            let right = serde_json::from_str(&pair[1]).unwrap();
+            let left = Data::parse(&pair[0]).unwrap();
+            let right = Data::parse(&pair[1]).unwrap();
             Pair { left, right }
         })
         .collect()
@@ -144,67 +80,12 @@ fn parse(r: impl BufRead) -> Vec<Pair> {
 mod test {
     use std::cmp::Ordering;
 
-    use crate::{is_in_order, Data};
-
-    // #[test]
-    // fn simple() {
-    //     assert!(is_in_order(&[Data::Int(1)], &[Data::Int(2)]).unwrap());
-    //     assert!(is_in_order(&[Data::Int(1)], &[Data::Int(1)]).is_none());
-    // }
-    // #[test]
-    // fn case1() {
-    //     let left = [1, 1, 3, 1, 1].map(Data::Int);
-    //     let right = [1, 1, 5, 1, 1].map(Data::Int);
-
-    //     assert!(is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case2() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[[1],[2,3,4]]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[[1],4]").unwrap();
-    //     assert!(is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case3() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[9]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[[8,7,6]]").unwrap();
-    //     assert!(!is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case4() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[[4,4],4,4]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[[4,4],4,4,4]").unwrap();
-    //     assert!(is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case5() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[7,7,7,7]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[7,7,7]").unwrap();
-    //     assert!(!is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case6() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[3]").unwrap();
-    //     assert!(is_in_order(&left, &right).unwrap());
-    // }
-
-    // #[test]
-    // fn case7() {
-    //     let left = serde_json::from_str::<Vec<Data>>("[[[]]]").unwrap();
-    //     let right = serde_json::from_str::<Vec<Data>>("[[]]").unwrap();
-    //     assert!(!is_in_order(&left, &right).unwrap());
-    // }
+    use crate::Data;
 
     #[test]
     fn case8() {
-        let left = serde_json::from_str::<Vec<Data>>("[1,[2,[3,[4,[5,6,7]]]],8,9]").unwrap();
-        let right = serde_json::from_str::<Vec<Data>>("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap();
-        assert_eq!(is_in_order(&left, &right), Ordering::Greater);
+        let left = Data::parse("[1,[2,[3,[4,[5,6,7]]]],8,9]").unwrap();
+        let right = Data::parse("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap();
+        assert_eq!(left.cmp_promoting(&right), Ordering::Greater);
     }
 }
@@ -1,149 +1,33 @@
-use std::collections::HashMap;
-
-use aoc::{fetch_input, text};
+use aoc::{
+    fetch_input, text,
+    two::{pt, CubeNet, DenseField, Dirn, Leniency},
+};
 
 #[derive(Debug)]
 struct Map {
-    width: usize,
-    height: usize,
-    data: Vec<Cell>,
-    warps: HashMap<Warp, Warp>,
-}
-
-#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-struct Warp {
-    side: usize,
-    edge: Facing,
-}
-impl Warp {
-    fn new(side: usize, edge: Facing) -> Warp {
-        Warp { side, edge }
-    }
-}
-
-fn warps() -> HashMap<Warp, Warp> {
-    use Facing::*;
-    let mut map = HashMap::new();
-    map.insert(Warp::new(1, Up), Warp::new(9, Left)); // x -> side - y
-    map.insert(Warp::new(1, Right), Warp::new(2, Left)); // y -> y
-    map.insert(Warp::new(1, Down), Warp::new(4, Up));
-    map.insert(Warp::new(1, Left), Warp::new(6, Left));
-    map.insert(Warp::new(2, Up), Warp::new(9, Down));
-    map.insert(Warp::new(2, Right), Warp::new(7, Right));
-    map.insert(Warp::new(2, Down), Warp::new(4, Right));
-    map.insert(Warp::new(4, Down), Warp::new(7, Up));
-    map.insert(Warp::new(4, Left), Warp::new(6, Up));
-    map.insert(Warp::new(6, Right), Warp::new(7, Left)); // y -> y
-    map.insert(Warp::new(6, Down), Warp::new(9, Up));
-    map.insert(Warp::new(7, Down), Warp::new(9, Right));
-
-    map.into_iter()
-        .flat_map(|(k, v)| [(k, v), (v, k)])
-        .collect()
+    field: DenseField<Cell>,
+    cube: CubeNet,
 }
 
 impl Map {
-    fn new(width: usize, height: usize, data: Vec<Cell>) -> Map {
-        Self {
-            width,
-            height,
-            data,
-            warps: warps(),
-        }
+    fn new(field: DenseField<Cell>) -> Map {
+        let cube = CubeNet::from_field(&field, |c| *c != Cell::Warp);
+        Self { field, cube }
     }
 
     fn get(&self, p: (isize, isize)) -> Cell {
-        if p.0 < 0 || (p.0 as usize) >= self.width || p.1 < 0 || p.1 as usize >= self.height {
-            return Cell::Warp;
-        }
-        self.data[p.1 as usize * self.width + p.0 as usize]
+        self.field
+            .try_get(pt(p.0, p.1))
+            .copied()
+            .unwrap_or(Cell::Warp)
     }
 
-    // Gets a position, warping and returning the real position.
-    fn get_warped(&self, p: (isize, isize), facing: Facing) -> ((isize, isize), Facing, Cell) {
-        let cell = self.get(p);
-
-        match cell {
-            Cell::Free(_) => (p, facing, cell),
-            Cell::Wall => (p, facing, cell),
-            Cell::Warp => {
-                //
-                // Part 2:
-                //
-
-                // Need to get our current cube side
-                //
-                // This point is already outside the bounds of the side.
-                // We need to step back into the side, can go opposite the
-                // facing direction.
-                let (prev_x, prev_y) = peek(p, facing.invert());
-                let cube_x = prev_x / CUBE_SIDE;
-                let cube_y = prev_y / CUBE_SIDE;
-                let side = (cube_y * 3 + cube_x) as usize;
-
-                // Get where we're warping to
-                let warp_from = Warp { side, edge: facing };
-                let warp_to = self.warps[&warp_from];
-
-                // What are our side-local coordinates?
-                let local_x = prev_x % CUBE_SIDE;
-                let local_y = prev_y % CUBE_SIDE;
-                let w = CUBE_SIDE; // cube side length;
-                let w1 = w - 1;
-
-                // What do these coordinates transform into?
-                let warped = match facing {
-                    Facing::Up => match warp_to.edge {
-                        Facing::Up => (w1 - local_x, 0),
-                        Facing::Down => (local_x, w1),
-                        Facing::Left => (0, local_x),
-                        Facing::Right => (w1, w1 - local_x),
-                    },
-                    Facing::Down => match warp_to.edge {
-                        Facing::Up => (local_x, 0),
-                        Facing::Down => (w1 - local_x, w1),
-                        Facing::Left => (0, w1 - local_x),
-                        Facing::Right => (w1, local_x),
-                    },
-                    Facing::Left => match warp_to.edge {
-                        Facing::Up => (local_y, 0),
-                        Facing::Down => (w1, w1 - local_y),
-                        Facing::Left => (0, w1 - local_y),
-                        Facing::Right => (w1, local_y),
-                    },
-                    Facing::Right => match warp_to.edge {
-                        Facing::Up => (w1 - local_y, 0),
-                        Facing::Down => (local_y, w1),
-                        Facing::Left => (0, local_y),
-                        Facing::Right => (w1, w1 - local_y),
-                    },
-                };
-
-                // Note: The warp_to.edge is the inverse of the new direction we
-                // should be facing.
-
-                // warped is local, need to get the delta to add from the side
-                // number.
-                let side = warp_to.side;
-                let x = CUBE_SIDE * (side % 3) as isize + warped.0;
-                let y = CUBE_SIDE * (side / 3) as isize + warped.1;
-                let warped = (x, y);
-
-                (warped, warp_to.edge.invert(), self.get(warped))
-
-                // Part 1:
-                // We need to find our real destination. We need to find the
-                // free/wall cell before the next warp block we see, travelling
-                // in the opposite direction to the way we're facing.
-                // let search_dir = facing.invert();
-                // let mut next = peek(p, search_dir);
-                // while self.get(next) != Cell::Warp {
-                //     next = peek(next, search_dir);
-                // }
-                // let previous = peek(next, facing);
-                // (previous, self.get(previous))
-            }
-        }
+    // Steps from a position, warping around the cube if we walk off a side.
+    fn step(&self, p: (isize, isize), facing: Facing) -> ((isize, isize), Facing, Cell) {
+        // Part 2, where the map is folded into a cube.
+        let (next, dirn) = self.cube.step(pt(p.0, p.1), facing.into());
+        let next = (next.x, next.y);
+        (next, dirn.into(), self.get(next))
     }
 }
 
@@ -187,18 +71,29 @@ impl Facing {
             Facing::Right => Facing::Down,
         };
     }
+}
 
-    fn invert(&self) -> Facing {
-        match self {
-            Facing::Up => Facing::Down,
-            Facing::Down => Facing::Up,
-            Facing::Left => Facing::Right,
-            Facing::Right => Facing::Left,
+impl From<Facing> for Dirn {
+    fn from(facing: Facing) -> Self {
+        match facing {
+            Facing::Up => Dirn::Up,
+            Facing::Down => Dirn::Down,
+            Facing::Left => Dirn::Left,
+            Facing::Right => Dirn::Right,
         }
     }
 }
 
-const CUBE_SIDE: isize = 50;
+impl From<Dirn> for Facing {
+    fn from(dirn: Dirn) -> Self {
+        match dirn {
+            Dirn::Up => Facing::Up,
+            Dirn::Down => Facing::Down,
+            Dirn::Left => Facing::Left,
+            Dirn::Right => Facing::Right,
+        }
+    }
+}
 
 fn main() {
     let (mut map, instr) = parse(&text(fetch_input(2022, 22)));
@@ -221,8 +116,7 @@ fn part2(map: &mut Map, instructions: &[Instr]) -> isize {
             Instr::Right => facing.turn_right(),
             Instr::Walk(n) => {
                 for _ in 0..*n {
-                    let next = peek(pos, facing);
-                    let (next, next_facing, cell) = map.get_warped(next, facing);
+                    let (next, next_facing, cell) = map.step(pos, facing);
                     match cell {
                         Cell::Free(_) => {
                             // map.set(pos, Cell::Free(facing.symbol()));
@@ -250,19 +144,10 @@ fn part2(map: &mut Map, instructions: &[Instr]) -> isize {
     row * 1000 + 4 * col + facing
 }
 
-fn peek(pos: (isize, isize), facing: Facing) -> (isize, isize) {
-    match facing {
-        Facing::Up => (pos.0, pos.1 - 1),
-        Facing::Down => (pos.0, pos.1 + 1),
-        Facing::Left => (pos.0 - 1, pos.1),
-        Facing::Right => (pos.0 + 1, pos.1),
-    }
-}
-
 fn get_start_position(map: &Map) -> (isize, isize) {
-    for x in 0..map.width {
-        if matches!(map.get((x as isize, 0)), Cell::Free(_)) {
-            return (x as isize, 0);
+    for x in 0..map.field.width() {
+        if matches!(map.get((x, 0)), Cell::Free(_)) {
+            return (x, 0);
         }
     }
 
@@ -276,23 +161,15 @@ fn parse(input: &str) -> (Map, Vec<Instr>) {
         lines.pop();
     }
 
-    let height = lines.len();
-    let width = lines.iter().map(|l| l.len()).max().unwrap();
-
-    let data: Vec<Cell> = lines
-        .into_iter()
-        .flat_map(|line| {
-            let mut line = line.as_bytes().to_vec();
-            line.resize(width, b' ');
-            line
-        })
-        .map(|b| match b {
-            b' ' => Cell::Warp,
-            b'#' => Cell::Wall,
-            b'.' => Cell::Free('.'),
-            _ => panic!(),
-        })
-        .collect();
+    // Rows stop at the last face, so pad them out to a rectangle.
+    let lines = lines.into_iter().map(String::from).collect();
+    let field = DenseField::try_from_lines_with_p(lines, Leniency::padded(b' '), |b, _| match b {
+        b' ' => Cell::Warp,
+        b'#' => Cell::Wall,
+        b'.' => Cell::Free('.'),
+        _ => panic!(),
+    })
+    .unwrap();
 
     let mut instrs = vec![];
     let mut token = String::new();
@@ -320,5 +197,5 @@ fn parse(input: &str) -> (Map, Vec<Instr>) {
         instrs.push(Instr::Walk(token.parse().unwrap()));
     }
 
-    (Map::new(width, height, data), instrs)
+    (Map::new(field), instrs)
 }
@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use aoc::{fetch_input, text};
+use aoc::{
+    fetch_input, text,
+    two::{self, pt, PeriodicField},
+};
 
 fn main() {
     // Written about this in obsidian notebook.
@@ -36,7 +39,7 @@ fn find_shortest(
 
     println!("Searching start {:?}", start);
 
-    while map.get_at(x, y, start.elapsed) != NONE {
+    while map.blizzards.is_blocked(pt(x, y), start.elapsed) {
         println!("Had to skip {:?}", start);
         start.elapsed += 1;
     }
@@ -123,7 +126,7 @@ fn expand(q: &mut HashSet<Node>, root: &Node, map: &Map) {
 }
 
 fn make_child(x: isize, y: isize, t: usize, map: &Map) -> Option<Node> {
-    if x >= 0 && x < map.width && y >= 0 && y < map.height && map.get_at(x, y, t) == NONE {
+    if map.blizzards.is_free(pt(x, y), t) {
         Some(Node {
             x,
             y,
@@ -147,7 +150,7 @@ fn parse(input: &str) -> Map {
     let height = (input.lines().count() - 2) as isize;
     let width = (input.lines().next().unwrap().len() - 2) as isize;
 
-    let data = input
+    let data: Vec<Cell> = input
         .lines()
         .skip(1)
         .take(height as usize)
@@ -167,9 +170,28 @@ fn parse(input: &str) -> Map {
         })
         .collect();
 
+    // Precompute where every blizzard is over a whole cycle.
+    let movers: Vec<_> = data
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &cell)| {
+            let p = pt(i as isize % width, i as isize / width);
+            [
+                (LEFT, two::LEFT),
+                (RIGHT, two::RIGHT),
+                (UP, two::UP),
+                (DOWN, two::DOWN),
+            ]
+            .into_iter()
+            .filter(move |(bit, _)| cell & bit != 0)
+            .map(move |(_, velocity)| (p, velocity))
+        })
+        .collect();
+
     Map {
         height,
         width,
+        blizzards: PeriodicField::from_movers(width, height, movers),
         data,
     }
 }
@@ -181,6 +203,7 @@ struct Map {
     data: Vec<Cell>,
     width: isize,
     height: isize,
+    blizzards: PeriodicField,
 }
 
 impl Map {
@@ -275,6 +298,15 @@ mod test {
         assert_eq!(DOWN, map.get_at(9, 2, 1));
 
         assert_eq!(DOWN | LEFT | RIGHT, map.get_at(7, 2, 1));
+
+        for t in 0..2 * map.blizzards.period() {
+            for y in 0..map.height {
+                for x in 0..map.width {
+                    let blocked = map.get_at(x, y, t) != NONE;
+                    assert_eq!(map.blizzards.is_blocked(pt(x, y), t), blocked);
+                }
+            }
+        }
     }
 
     #[test]
@@ -1,17 +1,16 @@
-use std::{
-    collections::HashMap,
-    fmt::{Display, Write},
-};
-
 use aoc::{
+    cycle::run_until,
     lines,
-    two::{pt, DenseField},
+    two::{DenseField, Dirn, GridCell},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Eq, GridCell)]
 enum Cell {
+    #[cell(b'O')]
     RoundRock,
+    #[cell(b'#')]
     SquareRock,
+    #[cell(b'.')]
     Empty,
 }
 
@@ -30,39 +29,15 @@ fn main() {
 
 const BILLION: usize = 1_000_000_000;
 
-fn part2(mut field: DenseField<Cell>) -> usize {
-    let mut seen = HashMap::new();
-    let mut cycle = 0;
-    let mut jumped = false;
-    loop {
-        if cycle == BILLION {
-            break;
-        }
-
-        if let Some(seen_before) = seen.insert(field.clone(), cycle) {
-            if !jumped {
-                jumped = true;
-
-                // We've seen this field before!
-                // We can now jump close to the final cycle.
-                let cycle_len = cycle - seen_before;
-                let cycles_left = BILLION - cycle;
-                let loops_remaining = cycles_left / cycle_len;
-                cycle += loops_remaining * cycle_len + 1;
-            } else {
-                cycle += 1;
-            }
-        } else {
-            cycle += 1;
-        }
+fn part2(field: DenseField<Cell>) -> usize {
+    let field = run_until(field, spin, BILLION);
+    north_support_load(&field)
+}
 
-        for _ in 0..4 {
-            roll_north(&mut field);
-            field.rotate_clockwise();
-        }
+fn spin(field: &mut DenseField<Cell>) {
+    for dirn in [Dirn::Up, Dirn::Left, Dirn::Down, Dirn::Right] {
+        roll(field, dirn);
     }
-
-    north_support_load(&field)
 }
 
 fn north_support_load(field: &DenseField<Cell>) -> usize {
@@ -78,53 +53,11 @@ fn north_support_load(field: &DenseField<Cell>) -> usize {
 }
 
 fn roll_north(field: &mut DenseField<Cell>) {
-    // For each row starting from the north (y=0) move rounded rocks until they
-    // hit something, or the top.
-    for y in 0..field.height() {
-        for x in 0..field.width() {
-            match field.get(pt(x, y)) {
-                Cell::RoundRock => {
-                    // go down from y to 0 here as much as we can.
-                    let mut fell_off = true;
-                    for i in 0..y {
-                        if *field.get(pt(x, y - i - 1)) != Cell::Empty {
-                            *field.get_mut(pt(x, y)) = Cell::Empty;
-                            *field.get_mut(pt(x, y - i)) = Cell::RoundRock;
-                            fell_off = false;
-                            break;
-                        }
-                    }
-                    if fell_off {
-                        *field.get_mut(pt(x, y)) = Cell::Empty;
-                        *field.get_mut(pt(x, 0)) = Cell::RoundRock;
-                    }
-                }
-                Cell::SquareRock => {}
-                Cell::Empty => {}
-            }
-        }
-    }
+    roll(field, Dirn::Up);
 }
 
-impl From<u8> for Cell {
-    fn from(value: u8) -> Self {
-        match value {
-            b'#' => Cell::SquareRock,
-            b'O' => Cell::RoundRock,
-            b'.' => Cell::Empty,
-            _ => panic!(),
-        }
-    }
-}
-
-impl Display for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char(match self {
-            Cell::RoundRock => 'O',
-            Cell::SquareRock => '#',
-            Cell::Empty => '.',
-        })
-    }
+fn roll(field: &mut DenseField<Cell>, dirn: Dirn) {
+    field.tilt(dirn, |c| *c == Cell::RoundRock, |c| *c == Cell::SquareRock);
 }
 
 #[cfg(test)]
@@ -1,8 +1,8 @@
-use std::collections::{HashMap, VecDeque};
-
 use aoc::{
     lines,
-    two::{pt, DenseField, Dirn, Point, DOWN, RIGHT},
+    queue::BucketQueue,
+    search::dijkstra_with,
+    two::{pt, DenseField, Dirn, Point},
 };
 
 fn conv(value: u8) -> usize {
@@ -22,62 +22,73 @@ fn main() {
     dbg!(part2);
 }
 
+/// Cheapest heat loss to the bottom right, having to go at least `min` and
+/// at most `max` in a straight line.
 fn search(field: &DenseField<usize>, min: u32, max: u32) -> usize {
-    let mut q = VecDeque::new();
-    let mut seen = HashMap::<(Point<isize>, Point<isize>, u32), usize>::new();
-
-    let mut min_loss_at_exit = usize::MAX;
+    let exit = pt(field.width() - 1, field.height() - 1);
 
-    // Start top left, no momentum.
-    q.push_back((pt(0, 0), RIGHT, 0, 0_usize));
-    q.push_back((pt(0, 0), DOWN, 0, 0_usize));
+    // Start top left, no momentum, so any direction is fine.
+    let path = dijkstra_with(
+        BucketQueue::new(),
+        (pt(0, 0), pt(0, 0), 0),
+        |&(p, dir, momentum): &(Point<isize>, Point<isize>, u32)| {
+            let mut next = vec![];
+            for next_dir in Dirn::all() {
+                if next_dir == -dir {
+                    // can't go backwards
+                    continue;
+                }
 
-    while let Some((p, dir, momentum, loss)) = q.pop_back() {
-        if let Some(&prev_loss) = seen.get(&(p, dir, momentum)) {
-            // Already seen this and got there faster!
-            if prev_loss <= loss {
-                continue;
-            }
+                // If we change direction, momentum will be 1 at the new location.
+                let next_momentum = if dir == next_dir { momentum + 1 } else { 1 };
+                let next_p = p + next_dir;
+                let Some(&loss) = field.try_get(next_p) else {
+                    continue; // this isn't on the field.
+                };
 
-            // No point exploring this route if it's already worse than a
-            // complete one we've seen.
-            if loss >= min_loss_at_exit {
-                continue;
+                if next_dir == dir {
+                    if next_momentum <= max {
+                        next.push(((next_p, next_dir, next_momentum), loss))
+                    }
+                } else if momentum >= min || momentum == 0 {
+                    next.push(((next_p, next_dir, next_momentum), loss))
+                }
             }
-        }
-
-        seen.insert((p, dir, momentum), loss);
+            next
+        },
+        |&(p, _, momentum)| p == exit && momentum >= min,
+    );
 
-        if p == pt(field.width() - 1, field.height() - 1) && momentum >= min {
-            min_loss_at_exit = min_loss_at_exit.min(loss);
-            continue;
-        }
+    path.unwrap().cost
+}
 
-        for next_dir in Dirn::all() {
-            if next_dir == -dir {
-                // can't go backwards
-                continue;
-            }
+#[cfg(test)]
+mod test {
+    use aoc::{lines_from_str, two::DenseField};
 
-            // If we change direction, momentum will be 1 at the new location.
-            let next_momentum = if dir == next_dir { momentum + 1 } else { 1 };
-            let next_p = p + next_dir;
-            let next_loss = loss
-                + if let Some(cell) = field.try_get(next_p) {
-                    cell
-                } else {
-                    continue; // this isn't on the field.
-                };
+    use crate::{conv, search};
 
-            if next_dir == dir {
-                if next_momentum <= max {
-                    q.push_back((next_p, next_dir, next_momentum, next_loss))
-                }
-            } else if momentum >= min {
-                q.push_back((next_p, next_dir, next_momentum, next_loss))
-            }
-        }
+    #[test]
+    fn example() {
+        let field = DenseField::from_lines_with(
+            lines_from_str(
+                "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533",
+            ),
+            conv,
+        );
+        assert_eq!(search(&field, 0, 3), 102);
+        assert_eq!(search(&field, 4, 10), 94);
     }
-
-    min_loss_at_exit
 }
@@ -1,3 +1,5 @@
+use std::{env, path::Path};
+
 use aoc::{
     fetch_input, lines,
     two::{pt, DenseField, IPoint},
@@ -37,6 +39,15 @@ fn main() {
         if found_line {
             println!("\nBelow is second {i}");
             picture.debug_print();
+
+            // Easier to see in an image viewer than the terminal.
+            if let Ok(dir) = env::var("AOC_IMAGE_DIR") {
+                let path = Path::new(&dir).join("day14.ppm");
+                picture
+                    .write_image(&path, 4, |c| if *c == '#' { [0, 160, 0] } else { [0; 3] })
+                    .unwrap();
+                println!("Also saved to {}", path.display());
+            }
             println!("If you see a christmas tree... part2 = {i}");
             break;
         }
@@ -2,7 +2,7 @@ use std::fmt::{Display, Write};
 
 use aoc::{
     fetch_input, line_blocks,
-    two::{DenseField, Dirn, IPoint, LEFT, RIGHT},
+    two::{DenseField, Dirn, IPoint, Push, LEFT, RIGHT},
 };
 use itertools::Itertools;
 
@@ -40,148 +40,15 @@ fn main() {
 
 fn move_robot_p2(field: &mut DenseField<Cell>, robot: &mut IPoint, dirn: Dirn) {
     assert_eq!(*field.get(*robot), Cell::Robot);
-    let destp = *robot + dirn.as_point();
-    let dest = field.get(destp).clone();
-
-    match dest {
-        Cell::Robot => panic!(),
-        Cell::Wall => {} // can't do anything.
-        Cell::BoxRight | Cell::BoxLeft => {
-            let box_left = match dest {
-                Cell::BoxLeft => destp,
-                Cell::BoxRight => destp + LEFT,
-                _ => panic!(),
-            };
-            if shove_box(field, box_left, dirn, false) {
-                *field.get_mut(destp) = Cell::Robot;
-                *field.get_mut(*robot) = Cell::Empty;
-                *robot = destp;
-            }
-        }
-        Cell::Empty => {
-            // Just move.
-            *field.get_mut(destp) = Cell::Robot;
-            *field.get_mut(*robot) = Cell::Empty;
-            *robot = destp;
-        }
-    }
-}
-
-fn move_box(field: &mut DenseField<Cell>, left_src: IPoint, left_dst: IPoint) {
-    assert_eq!(*field.get(left_src), Cell::BoxLeft);
-    *field.get_mut(left_src) = Cell::Empty;
-    *field.get_mut(left_src + RIGHT) = Cell::Empty;
-    *field.get_mut(left_dst) = Cell::BoxLeft;
-    *field.get_mut(left_dst + RIGHT) = Cell::BoxRight;
-}
-
-fn shove_box(field: &mut DenseField<Cell>, box_left: IPoint, dirn: Dirn, dry: bool) -> bool {
-    let mov = |field: &mut DenseField<Cell>| {
-        if dry {
-            return;
-        };
-        move_box(field, box_left, box_left + dirn.as_point());
-    };
-
-    let handle_vert = |field: &mut DenseField<Cell>, dirn: Dirn| {
-        let blocker_left = field.get(box_left + dirn.as_point()).clone();
-        let blocker_right = field.get(box_left + dirn.as_point() + RIGHT).clone();
-
-        match blocker_left {
-            Cell::Wall => return false,
-            Cell::Empty => {} // fine here, still need to look at other blocker.
-            Cell::BoxLeft => {
-                // simple case, we're directly below another box, so just
-                // need to check this one.
-                assert_eq!(blocker_right, Cell::BoxRight);
-                if shove_box(field, box_left + dirn.as_point(), dirn, dry) {
-                    mov(field);
-                    return true;
-                } else {
-                    return false;
-                }
-            }
-            Cell::BoxRight => {
-                // complex case, got to check other side too.
-                if shove_box(field, box_left + dirn.as_point() + LEFT, dirn, true) {
-                    // can't move yet, right may be blocked.
-                } else {
-                    return false;
-                }
-            }
-            Cell::Robot => panic!(),
-        }
-
-        match blocker_right {
-            Cell::Wall => false,
-            Cell::BoxLeft => {
-                if shove_box(field, box_left + dirn.as_point() + RIGHT, dirn, dry) {
-                    if *field.get(box_left + dirn.as_point() + LEFT) == Cell::BoxLeft {
-                        assert!(shove_box(
-                            field,
-                            box_left + dirn.as_point() + LEFT,
-                            dirn,
-                            dry
-                        ));
-                    }
-                    mov(field); // can finally move it
-                    true
-                } else {
-                    false
-                }
-            }
-            Cell::Empty => {
-                if *field.get(box_left + dirn.as_point() + LEFT) == Cell::BoxLeft {
-                    assert!(shove_box(
-                        field,
-                        box_left + dirn.as_point() + LEFT,
-                        dirn,
-                        dry
-                    ));
-                }
-                mov(field);
-                true
-            }
-            Cell::BoxRight => panic!(), // should have been dealt with above.
-            Cell::Robot => panic!(),
-        }
-    };
-
-    match dirn {
-        Dirn::Up | Dirn::Down => handle_vert(field, dirn),
-        Dirn::Right => match field.get(box_left + 2 * dirn.as_point()).clone() {
-            Cell::Wall => false,
-            Cell::BoxLeft => {
-                // Otherwise shove the box in the way if we can.
-                if shove_box(field, box_left + 2 * dirn.as_point(), dirn, dry) {
-                    mov(field);
-                    true
-                } else {
-                    false
-                }
-            }
-            Cell::Empty => {
-                mov(field);
-                true
-            }
-            _ => panic!(),
-        },
-        Dirn::Left => match field.get(box_left + LEFT).clone() {
-            Cell::Wall => false,
-            Cell::BoxRight => {
-                if shove_box(field, box_left + 2 * dirn.as_point(), dirn, dry) {
-                    mov(field);
-                    true
-                } else {
-                    false
-                }
-            }
-            Cell::Empty => {
-                mov(field);
-                true
-            }
-            _ => panic!(),
-        },
+    let pushed = field.push_chain(*robot, dirn, |p, cell| match cell {
+        Cell::Wall => Push::Blocked,
+        Cell::Empty => Push::Empty,
+        Cell::Robot => Push::Moves(vec![p]),
+        Cell::BoxLeft => Push::Moves(vec![p, p + RIGHT]),
+        Cell::BoxRight => Push::Moves(vec![p + LEFT, p]),
+    });
+    if pushed {
+        *robot += dirn.as_point();
     }
 }
 
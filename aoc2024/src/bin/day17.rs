@@ -1,6 +1,6 @@
 use core::panic;
 
-use aoc::{fetch_input, line_blocks, StrExt};
+use aoc::{fetch_input, sections::sections, StrExt};
 use itertools::Itertools;
 
 #[derive(Debug, Clone)]
@@ -11,9 +11,8 @@ struct Registers {
 }
 
 fn main() {
-    let input = line_blocks(fetch_input(2024, 17));
-    let registers = parse_registers(&input[0]);
-    let instructions = parse_instructions(&input[1]);
+    let (registers, instructions) =
+        sections(fetch_input(2024, 17), (parse_registers, parse_instructions));
 
     let output = simulate(registers, &instructions);
     let part1 = output.into_iter().map(|v| v.to_string()).join(",");
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use aoc::{
     fetch_input, lines,
-    two::{pt, DenseField, IPoint},
+    two::{pt, DenseField, IPoint, Pattern},
 };
 
 fn main() {
@@ -17,31 +17,15 @@ fn main() {
 }
 
 fn part2(field: &DenseField<u8>) -> HashSet<IPoint> {
-    let mut xmases = HashSet::<IPoint>::new(); // point of central A.
-
-    for p in field.points() {
-        if field.get(p) == &b'A' {
-            let neigh = [p + pt(1, 1), p + pt(-1, 1), p + pt(1, -1), p + pt(-1, -1)];
-            let mut count = 0;
-
-            for n in neigh {
-                if let Some(&b'M') = field.try_get(n) {
-                    let other = p - (n - p);
-                    let val = field.try_get(other);
-
-                    if let Some(b'S') = val {
-                        count += 1;
-                    }
-                }
-            }
-
-            if count == 2 {
-                xmases.insert(p);
-            }
-        }
-    }
-
-    xmases
+    // Any rotation of the X, with the middle A at (1, 1).
+    let x_mas = ["M.S", ".A.", "M.S"].map(String::from).to_vec();
+    let pattern = Pattern::from_lines_all_orientations(x_mas, b'.');
+
+    field
+        .find_pattern(&pattern)
+        .iter()
+        .map(|m| m.at + pt(1, 1))
+        .collect()
 }
 
 fn part1(field: &DenseField<u8>) -> HashSet<(IPoint, IPoint)> {
@@ -49,24 +33,11 @@ fn part1(field: &DenseField<u8>) -> HashSet<(IPoint, IPoint)> {
 
     for p in field.points() {
         if field.get(p) == &b'X' {
-            // Found the X, do any neighbours give us the M?
-            let neigh = field.neighbours8_bounded(p);
-            'neighs: for n in neigh {
-                if field.get(n.1) == &b'M' {
-                    // Found the M, we now have a direction to look in for the
-                    // extra characters.
-                    let dirn = n.1 - p;
-                    let rest = [b'A', b'S'];
-                    for (i, ch) in rest.iter().enumerate() {
-                        let offset = dirn * (i + 2) as isize; // +1 is the M, +2 is the A...
-                        let probe = p + offset;
-                        let val = field.try_get(probe);
-                        if val.is_none() || val.unwrap() != ch {
-                            continue 'neighs; // this direction isn't XMAS.
-                        }
-                    }
-                    // if we got here, we found XMAS in this direction.
-                    xmases.insert((p, p + (dirn * 4)));
+            // Look along every direction from the X for the rest of the word.
+            for ray in field.rays8(p) {
+                let rest: Vec<_> = ray.take(3).collect();
+                if rest.iter().map(|(_, c)| **c).eq(*b"MAS") {
+                    xmases.insert((p, rest[2].0));
                 }
             }
         }
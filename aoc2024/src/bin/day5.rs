@@ -1,13 +1,11 @@
 use std::cmp::Ordering;
 
 use aoc::StrExt;
-use aoc::{fetch_input, line_blocks};
+use aoc::{fetch_input, sections::sections};
 use itertools::Itertools;
 
 fn main() {
-    let input = line_blocks(fetch_input(2024, 5));
-    let rules = parse_rules(&input[0]);
-    let updates = parse_updates(&input[1]);
+    let (rules, updates) = sections(fetch_input(2024, 5), (parse_rules, parse_updates));
 
     let (ordered, unordered): (Vec<_>, _) = updates
         .into_iter()
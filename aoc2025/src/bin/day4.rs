@@ -1,6 +1,7 @@
-use std::usize;
-
-use aoc::{fetch_input, lines, two::DenseField};
+use aoc::{
+    fetch_input, lines,
+    two::{Automaton, DenseField},
+};
 
 fn main() {
     let input = lines(fetch_input(2025, 4));
@@ -36,32 +37,16 @@ fn part1(field: &DenseField<char>) -> usize {
     count
 }
 
-fn part2(mut field: DenseField<char>) -> usize {
-    let mut count = 0;
-
-    let mut removed_this_iteration = usize::MAX; // any positive number
-
-    while removed_this_iteration > 0 {
-        removed_this_iteration = 0;
-
-        for p in field.points() {
-            if *field.get(p) != '@' {
-                continue;
-            }
+fn part2(field: DenseField<char>) -> usize {
+    let rolls = |f: &DenseField<char>| f.data().iter().filter(|c| **c == '@').count();
+    let before = rolls(&field);
 
-            let adj_rolls = field
-                .neighbours8_bounded(p)
-                .filter(|(c, _)| **c == '@')
-                .count();
+    // Keep removing any roll with fewer than four rolls around it.
+    let mut automaton = Automaton::new(field);
+    automaton.run_until_stable(|n| match n.value() {
+        '@' if n.count(|c| *c == '@') < 4 => '.',
+        c => *c,
+    });
 
-            if adj_rolls < 4 {
-                removed_this_iteration += 1;
-                *field.get_mut(p) = '.';
-            }
-        }
-
-        count += removed_this_iteration;
-    }
-
-    count
+    before - rolls(automaton.field())
 }